@@ -0,0 +1,156 @@
+//! Redraws simulation frames in place in the terminal.
+//!
+//! While the animation runs, entering a line on stdin pauses it. When paused `c` continues,
+//! `q` stops the animation, letting the simulation finish without drawing, and any other line
+//! (an empty one will do) advances a single frame.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+/// Wraps the text in escape codes making it stand out.
+pub fn highlight(s: &str) -> String {
+    format!("{}{}{}", HIGHLIGHT, s, RESET)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Pause,
+    Next,
+    Continue,
+    Quit,
+}
+
+impl Control {
+    fn parse(line: &str, paused: bool) -> Control {
+        match line.trim() {
+            "q" => Control::Quit,
+            "c" if paused => Control::Continue,
+            _ if paused => Control::Next,
+            _ => Control::Pause,
+        }
+    }
+}
+
+pub struct Animation {
+    delay: Duration,
+    drawn_lines: usize,
+    paused: bool,
+    stopped: bool,
+    controls: Option<Receiver<String>>,
+}
+
+impl Animation {
+    /**
+    Animation showing `fps` frames per second. Controls are read from stdin only when it is a
+    terminal and `controls` is set, it must be off when the puzzle input itself comes from stdin.
+     **/
+    pub fn new(fps: u32, controls: bool) -> Animation {
+        let controls = (controls && io::stdin().is_terminal()).then(|| {
+            let (tx, rx) = mpsc::channel();
+
+            thread::spawn(move || {
+                for line in io::stdin().lock().lines() {
+                    if line.ok().and_then(|l| tx.send(l).ok()).is_none() {
+                        break;
+                    }
+                }
+            });
+
+            rx
+        });
+
+        Animation {
+            delay: Duration::from_secs(1) / fps.max(1),
+            drawn_lines: 0,
+            paused: false,
+            stopped: false,
+            controls,
+        }
+    }
+
+    /// Replaces the previous frame with this one and waits for the next frame to be due.
+    pub fn frame(&mut self, frame: &str) {
+        if self.stopped {
+            return;
+        }
+
+        self.draw(frame).expect("Unable to draw the frame");
+        self.wait();
+    }
+
+    fn draw(&mut self, frame: &str) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+
+        if self.drawn_lines > 0 {
+            // Move up to the first line of the previous frame and clear everything below
+            write!(out, "\x1b[{}A\x1b[J", self.drawn_lines)?;
+        }
+        writeln!(out, "{}", frame)?;
+        out.flush()?;
+
+        self.drawn_lines = frame.lines().count();
+        Ok(())
+    }
+
+    fn wait(&mut self) {
+        let rx = match &self.controls {
+            Some(rx) => rx,
+            None => return thread::sleep(self.delay),
+        };
+
+        if !self.paused {
+            thread::sleep(self.delay);
+        }
+
+        loop {
+            let line = if self.paused {
+                rx.recv().map_err(|_| TryRecvError::Disconnected)
+            } else {
+                rx.try_recv()
+            };
+
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => {
+                    // Nothing was typed, or stdin got closed and can't resume a pause
+                    self.paused = false;
+                    return;
+                }
+            };
+
+            // Typed lines are echoed, keep them out of the next redraw
+            self.drawn_lines += 1;
+
+            match Control::parse(&line, self.paused) {
+                Control::Pause => self.paused = true,
+                Control::Next => return,
+                Control::Continue => {
+                    self.paused = false;
+                    return;
+                }
+                Control::Quit => {
+                    self.stopped = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_controls() {
+        assert_eq!(Control::parse("\n", false), Control::Pause);
+        assert_eq!(Control::parse("", true), Control::Next);
+        assert_eq!(Control::parse("c", true), Control::Continue);
+        assert_eq!(Control::parse("q", false), Control::Quit);
+    }
+}
@@ -0,0 +1,167 @@
+//! Cooperative cancellation of long running solvers.
+//!
+//! [`with_timeout`] runs a solver on a worker thread and gives up waiting once the time is up,
+//! so a solver stuck on some input can't hang the command. The worker is told to stop as well:
+//! the main loops of the solvers call [`checkpoint`], which unwinds the worker once cancelled.
+//!
+//! A solver panicking is a bug, [`catch_panics`] reports it as [`AdventError::Bug`] instead of
+//! taking the whole command down.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::{input, log, mem, profile, AdventError};
+
+/// The solvers put large grids on the stack, give the workers as much as the main thread.
+pub(crate) const STACK_SIZE: usize = 64 << 20;
+
+/// Payload of the unwinding started by [`checkpoint`].
+struct Cancelled;
+
+thread_local! {
+    static TOKEN: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Stops the solver running on this thread if its time is up, a no-op outside of [`with_timeout`].
+pub fn checkpoint() {
+    let cancelled = TOKEN.with(|t| t.borrow().as_ref().is_some_and(|t| t.load(Ordering::Relaxed)));
+
+    if cancelled {
+        // Unlike panic! this skips the panic hook, nothing gets printed
+        panic::resume_unwind(Box::new(Cancelled));
+    }
+}
+
+/// Runs `f`, turning a panic into [`AdventError::Bug`].
+pub fn catch_panics<R, F: FnOnce() -> Result<R, AdventError>>(f: F) -> Result<R, AdventError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(bug(payload)))
+}
+
+fn bug(payload: Box<dyn Any + Send>) -> AdventError {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("the solver panicked", |m| m).to_string(),
+    };
+
+    AdventError::Bug(message)
+}
+
+/**
+Runs `f` on a worker thread, failing with [`AdventError::Timeout`] when it takes longer than
+`timeout`. Without a timeout `f` simply runs on the current thread. Either way a panic of `f`
+is reported as [`AdventError::Bug`].
+
+The lenient mode, skipped records, printed answers, allocations and, when [buffered](log::buffer),
+log messages of the worker are carried over to the current thread, as if `f` ran on it. Spans
+opened by `f` nest inside the ones open on the current thread.
+ **/
+pub fn with_timeout<R, F>(timeout: Option<Duration>, f: F) -> Result<R, AdventError>
+    where R: Send + 'static, F: FnOnce() -> Result<R, AdventError> + Send + 'static {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return catch_panics(f),
+    };
+
+    let token = Arc::new(AtomicBool::new(false));
+    let worker_token = token.clone();
+    let lenient = input::is_lenient();
+    let buffered = log::is_buffered();
+    let open = profile::open();
+    let (tx, rx) = mpsc::channel();
+
+    thread::Builder::new()
+        .name("solver".to_string())
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            TOKEN.with(|t| *t.borrow_mut() = Some(worker_token));
+            input::set_lenient(lenient);
+            profile::enter(open);
+
+            let run = || mem::measure(|| panic::catch_unwind(AssertUnwindSafe(|| log::capture(f))));
+            let ((res, stats), logs) = match buffered {
+                true => log::buffer(run),
+                false => (run(), String::new()),
+            };
+            // Nobody is listening anymore when the time ran out
+            let _ = tx.send((res, input::take_skipped(), logs, stats, mem::take_phases()));
+        })
+        .expect("Unable to start a solver thread");
+
+    match rx.recv_timeout(timeout) {
+        Ok((res, skipped, logs, stats, phases)) => {
+            input::add_skipped(skipped);
+            log::emit(&logs);
+            mem::add(stats);
+            mem::add_phases(phases);
+
+            match res {
+                Ok((res, answers)) => {
+                    log::replay(&answers);
+                    res
+                }
+                Err(payload) if payload.is::<Cancelled>() => Err(AdventError::Timeout(timeout)),
+                Err(payload) => Err(bug(payload)),
+            }
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            token.store(true, Ordering::Relaxed);
+            Err(AdventError::Timeout(timeout))
+        }
+        Err(RecvTimeoutError::Disconnected) => unreachable!("The solver thread catches every panic"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_cancels_the_worker() {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker_stopped = stopped.clone();
+
+        let res = with_timeout(Some(Duration::from_millis(50)), move || -> Result<(), AdventError> {
+            // Sets the flag while unwinding out of the endless loop
+            struct Guard(Arc<AtomicBool>);
+            impl Drop for Guard {
+                fn drop(&mut self) {
+                    self.0.store(true, Ordering::Relaxed);
+                }
+            }
+
+            let _guard = Guard(worker_stopped);
+            loop {
+                checkpoint();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        assert!(matches!(res, Err(AdventError::Timeout(_))));
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(stopped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_result_within_time() {
+        let res = with_timeout(Some(Duration::from_secs(10)), || Ok(42));
+
+        assert_eq!(res.unwrap(), 42);
+        checkpoint();
+    }
+
+    #[test]
+    fn test_panics_are_bugs() {
+        let res: Result<(), _> = with_timeout(Some(Duration::from_secs(10)), || panic!("index {} out of bounds", 3));
+        assert!(matches!(res, Err(AdventError::Bug(m)) if m == "index 3 out of bounds"));
+
+        let res: Result<(), _> = with_timeout(None, || panic!("oops"));
+        assert_eq!(res.unwrap_err().category().exit_code(), 101);
+    }
+}
@@ -0,0 +1,103 @@
+//! What the binary can solve: the `list` and `describe` commands, printing the solver metadata.
+
+use std::fmt::Write;
+use std::fs;
+
+use clap::Parser;
+
+use crate::solver::{self, Solver};
+use crate::store::{Dirs, Store};
+use crate::AdventError;
+
+#[derive(Parser)]
+pub struct SubCmd {
+    day: u8,
+
+    #[clap(flatten)]
+    dirs: Dirs,
+}
+
+/// One line per day with its parts and input format, followed by its parameters.
+pub fn list() -> String {
+    let mut res = String::new();
+    let width = solver::solvers().iter().map(|s| s.title.len()).max().unwrap_or(0);
+
+    for s in solver::solvers() {
+        writeln!(res, "{:>2}  {:<width$}  {:<5}  {}", s.day, s.title, parts(s), s.input, width = width)
+            .expect("Writing to a String can't fail");
+        for p in &s.params {
+            // Under the input format column
+            writeln!(res, "{:indent$}{} = {}", "", p.name, p.default, indent = width + 13)
+                .expect("Writing to a String can't fail");
+        }
+    }
+
+    res
+}
+
+/// Everything known about the day, with its example input when the data directory has it.
+pub fn describe(solver: &Solver, store: &Store) -> String {
+    let mut res = String::new();
+
+    writeln!(res, "Day {}: {}", solver.day, solver.title).expect("Writing to a String can't fail");
+    writeln!(res, "Parts: {}", parts(solver)).expect("Writing to a String can't fail");
+    writeln!(res, "Input: {}", solver.input).expect("Writing to a String can't fail");
+
+    if !solver.params.is_empty() {
+        res.push_str("Parameters, set in advent.toml or with --set:\n");
+        for p in &solver.params {
+            writeln!(res, "    {} = {}", p.name, p.default).expect("Writing to a String can't fail");
+        }
+    }
+
+    if !solver.commands.is_empty() {
+        res.push_str("Commands of the repl:\n");
+        let width = solver.commands.iter().map(|c| c.usage.len()).max().unwrap_or(0);
+        for c in &solver.commands {
+            writeln!(res, "    {:<width$}  {}", c.usage, c.help, width = width).expect("Writing to a String can't fail");
+        }
+    }
+
+    if solver.has_figure() {
+        res.push_str("Figure: drawn in the run-all --report page\n");
+    }
+
+    match store.find(solver.day, solver.example).and_then(|path| fs::read_to_string(&path).ok().map(|text| (path, text))) {
+        Some((path, text)) => writeln!(res, "Example input, {}:\n{}", path.display(), text.trim_end()).expect("Writing to a String can't fail"),
+        None => writeln!(res, "No {} input in the data directory to show as an example", solver.example).expect("Writing to a String can't fail"),
+    }
+
+    res
+}
+
+fn parts(solver: &Solver) -> &'static str {
+    match solver.has_part2() {
+        true => "1, 2",
+        false => "1",
+    }
+}
+
+pub fn main(args: SubCmd) -> Result<(), AdventError> {
+    let solver = solver::find(args.day)
+        .ok_or_else(|| AdventError::InvalidCommand(format!("No such day {}, see the list command", args.day)))?;
+    print!("{}", describe(solver, &Store::new(&args.dirs.data_dir, &args.dirs.cache_dir)));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let listing = list();
+        assert!(listing.starts_with(" 1  Sonar Sweep              1, 2   Depth measurements, one number per line\n"));
+        assert!(listing.contains("  day6.part2_days = 256\n"));
+
+        let text = describe(solver::find(8).unwrap(), &Store::new("data", "data/cache"));
+        assert!(text.starts_with("Day 8: Seven Segment Search\nParts: 1, 2\n"));
+        assert!(text.contains("Commands of the repl:\n    decode <signals> | <outputs>  Decodes a single display entry\n"));
+        assert!(text.contains("Example input, data/08_display_test.txt:\nbe cfbegad"));
+    }
+}
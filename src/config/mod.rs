@@ -0,0 +1,171 @@
+//! Settings read from `advent.toml`: parameters of the days and their default inputs.
+//!
+//! ```toml
+//! [inputs]
+//! day6 = "data/06_fishes_prod.txt"
+//!
+//! [day6]
+//! part2_days = 256
+//! ```
+//!
+//! Every setting is optional, see the `Config` struct of a day for its parameters and their
+//! defaults. `--set day6.part2_days=18` overrides a single setting from the command line, and
+//! an input given on the command line wins over the one of the file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::input::{Input, InputError};
+use crate::{day_01, day_06, day_11, day_14, solver};
+
+pub const DEFAULT_PATH: &str = "advent.toml";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Unable to read {}: {source}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+
+    #[error("Invalid configuration in {origin}: {message}")]
+    Invalid { origin: String, message: String },
+
+    #[error("Expected a setting like day6.part2_days=18, got {0:?}")]
+    Override(String),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Default input of a day, keyed `dayN`
+    pub inputs: BTreeMap<String, PathBuf>,
+    pub day1: day_01::Config,
+    pub day6: day_06::Config,
+    pub day11: day_11::Config,
+    pub day14: day_14::Config,
+    /// File the settings come from, if any
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+static CONFIG: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+impl Config {
+    /**
+    Reads the file at `path` and applies the `overrides`, each a `key=value` pair with a dotted
+    key. A missing file is only an error when `required`, otherwise the defaults are used.
+     **/
+    pub fn load<S: AsRef<str>>(path: &Path, required: bool, overrides: &[S]) -> Result<Config, ConfigError> {
+        let (text, origin) = match fs::read_to_string(path) {
+            Ok(text) => (text, Some(path.to_path_buf())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => (String::new(), None),
+            Err(source) => return Err(ConfigError::Read { path: path.to_path_buf(), source }),
+        };
+
+        let invalid = |e: &dyn ToString| ConfigError::Invalid { origin: path.display().to_string(), message: e.to_string().trim_end().to_string() };
+        let mut table: toml::Table = text.parse().map_err(|e| invalid(&e))?;
+        for o in overrides {
+            set(&mut table, o.as_ref())?;
+        }
+
+        let mut config: Config = table.try_into().map_err(|e| invalid(&e))?;
+        if let Some(key) = config.inputs.keys().find(|k| day_of(k).is_none()) {
+            return Err(invalid(&format!("Expected inputs to be keyed day1 to day{}, got {:?}", solver::solvers().len(), key)));
+        }
+        let checks = [config.day1.check(), config.day6.check(), config.day14.check()];
+        if let Some(message) = checks.into_iter().find_map(Result::err) {
+            return Err(invalid(&message));
+        }
+        config.path = origin;
+
+        Ok(config)
+    }
+
+    /// Default input of the day, if there is one.
+    pub fn input(&self, day: u8) -> Option<&Path> {
+        self.inputs.get(&format!("day{}", day)).map(PathBuf::as_path)
+    }
+
+    /// The file itself and the inputs it names, for watching.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.path.iter().chain(self.inputs.values()).cloned().collect()
+    }
+}
+
+fn day_of(key: &str) -> Option<u8> {
+    key.strip_prefix("day")?.parse().ok().filter(|&d| solver::find(d).is_some())
+}
+
+/// Sets the dotted key of an override, values which aren't valid TOML are taken as strings.
+fn set(table: &mut toml::Table, setting: &str) -> Result<(), ConfigError> {
+    let invalid = || ConfigError::Override(setting.to_string());
+    let (key, value) = setting.split_once('=').ok_or_else(invalid)?;
+    let value = format!("value = {}", value.trim()).parse::<toml::Table>().ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.trim().to_string()));
+
+    let mut keys: Vec<&str> = key.trim().split('.').collect();
+    let last = keys.pop().filter(|k| !k.is_empty()).ok_or_else(invalid)?;
+    let mut table = table;
+    for k in keys {
+        table = table.entry(k)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(invalid)?;
+    }
+    table.insert(last.to_string(), value);
+
+    Ok(())
+}
+
+/// Settings in effect, the defaults until [`install`]ed.
+pub fn get() -> Arc<Config> {
+    CONFIG.read().expect("Configuration lock poisoned").clone().unwrap_or_default()
+}
+
+/// Makes the settings the ones in effect for the whole process.
+pub fn install(config: Config) {
+    *CONFIG.write().expect("Configuration lock poisoned") = Some(Arc::new(config));
+}
+
+/// Input given on the command line, or else the default one of the configuration.
+pub fn input(day: u8, arg: Option<Input>) -> Result<Input, InputError> {
+    match (arg, get().input(day)) {
+        (Some(input), _) => Ok(input),
+        (None, Some(path)) => Ok(Input::path(path)),
+        (None, None) => Err(io::Error::new(io::ErrorKind::NotFound,
+                                           format!("No input given for day {} and none set in the configuration", day)).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_and_overrides() {
+        let path = std::env::temp_dir().join(format!("advent-config-{}.toml", std::process::id()));
+        fs::write(&path, "[inputs]\nday6 = \"data/06_fishes_test.txt\"\n\n[day6]\npart1_days = 18\n").unwrap();
+
+        let config = Config::load(&path, true, &["day6.part2_days = 80", "day14.part1_steps=5", "inputs.day1=data/01.txt"]).unwrap();
+        assert_eq!((config.day6.part1_days, config.day6.part2_days, config.day6.spawn_days), (18, 80, 6));
+        assert_eq!(config.day14.part1_steps, 5);
+        assert_eq!(config.input(1), Some(Path::new("data/01.txt")));
+        assert_eq!(config.files(), vec![path.clone(), PathBuf::from("data/01.txt"), PathBuf::from("data/06_fishes_test.txt")]);
+
+        assert!(matches!(Config::load(&path, true, &["day6.part2_days"]), Err(ConfigError::Override(_))));
+        assert!(matches!(Config::load(&path, true, &["day6.days=3"]), Err(ConfigError::Invalid { .. })));
+        assert!(matches!(Config::load(&path, true, &["inputs.day16=x"]), Err(ConfigError::Invalid { .. })));
+        for o in ["day1.window_offset=0", "day6.spawn_days=-1", "day6.part2_days=400", "day14.part2_steps=100"] {
+            assert!(matches!(Config::load(&path, true, &[o]), Err(ConfigError::Invalid { .. })), "{}", o);
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert!(Config::load(&path, false, &[] as &[&str]).unwrap().path.is_none());
+        assert!(matches!(Config::load(&path, true, &[] as &[&str]), Err(ConfigError::Read { .. })));
+    }
+}
@@ -1,33 +1,79 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+//! Day 1: Sonar Sweep
+
 use std::ops::Range;
-use std::path::Path;
-use std::str::FromStr;
 
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::{config, mem};
+use crate::input::{self, Input, InputError};
+use crate::{answer, debug};
+
 #[derive(Parser)]
 pub struct Day1SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
     #[clap(long, default_value = "1")]
     window_width: usize,
     #[clap(long, default_value = "1")]
     window_offset: usize,
 }
 
+/// Parameters of the `[day1]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Measurements summed by the sliding window of part 2
+    pub window_width: usize,
+    /// Measurements the sliding window moves by
+    pub window_offset: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { window_width: 3, window_offset: 1 }
+    }
+}
+
+impl Config {
+    /// Rejects windows which never move or sum nothing.
+    pub fn check(&self) -> Result<(), String> {
+        match (self.window_width, self.window_offset) {
+            (0, _) => Err("Expected day1.window_width to be at least 1".to_string()),
+            (_, 0) => Err("Expected day1.window_offset to be at least 1".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
 pub fn main(args: Day1SubCmd) -> Result<(), Day1Error> {
-    let numbers = read_lines(args.input_filename)?;
-    let window_sums = window_map(numbers, args.window_width, args.window_offset, |w| w.iter().sum());
+    let numbers = mem::phase("parse", || parse(&config::input(1, args.input_filename)?))?;
+    let window_sums = mem::phase("solve", || window_map(&numbers, args.window_width, args.window_offset, |w| w.iter().sum()));
     for ws in window_sums.iter() {
-        println!("{}", ws);
+        debug!("{}", ws);
     }
-    let increases = count_increases(window_sums);
-    println!("{} measurements that are larger than previous measurement", increases);
+    let increases = count_increases(&window_sums);
+    answer!("{} measurements that are larger than previous measurement", increases);
 
     Ok(())
 }
 
+/// Depth measurements, one number per line.
+pub fn parse(input: &Input) -> Result<Vec<i32>, Day1Error> {
+    Ok(input::parse_lines(input)?)
+}
+
+/// Number of measurements larger than the previous one.
+pub fn part1(numbers: &[i32]) -> i32 {
+    count_increases(numbers)
+}
+
+/// Number of increases between sums of a sliding window, three measurements wide by default.
+pub fn part2(numbers: &[i32], config: &Config) -> i32 {
+    count_increases(&window_map(numbers, config.window_width, config.window_offset, |w| w.iter().sum()))
+}
+
 struct IncCounter {
     increases: i32,
     prev_number: Option<i32>,
@@ -35,14 +81,10 @@ struct IncCounter {
 
 impl IncCounter {
     fn process_number(&self, num: i32) -> IncCounter {
-        if self.prev_number.is_some() {
-            if num > self.prev_number.unwrap() {
-                IncCounter { increases: self.increases + 1, prev_number: Option::Some(num) }
-            } else {
-                IncCounter { increases: self.increases, prev_number: Option::Some(num) }
-            }
-        } else {
-            IncCounter { increases: self.increases, prev_number: Option::Some(num) }
+        match self.prev_number {
+            Some(prev) if num > prev =>
+                IncCounter { increases: self.increases + 1, prev_number: Option::Some(num) },
+            _ => IncCounter { increases: self.increases, prev_number: Option::Some(num) },
         }
     }
 
@@ -51,7 +93,8 @@ impl IncCounter {
     }
 }
 
-fn count_increases(nums: Vec<i32>) -> i32 {
+/// Counts numbers which are larger than the one before them.
+pub fn count_increases(nums: &[i32]) -> i32 {
     nums.iter().fold(IncCounter::empty(), |acc, &x| acc.process_number(x)).increases
 }
 
@@ -90,40 +133,17 @@ impl WindowIndexer {
     }
 }
 
-fn window_map<A, B, F>(xs: Vec<A>, width: usize, offset: usize, f: F) -> Vec<B>
+/// Applies `f` to every window of `width` elements, advancing the window by `offset`.
+pub fn window_map<A, B, F>(xs: &[A], width: usize, offset: usize, f: F) -> Vec<B>
     where F: Fn(&[A]) -> B, {
-    let slice = xs.as_slice();
-
     WindowIndexer
     ::for_vec(width, offset, xs.len())
-        .map(|r| f(&slice[r]))
+        .map(|r| f(&xs[r]))
         .collect()
 }
 
 #[derive(Error, Debug)]
 pub enum Day1Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
-
-    #[error(transparent)]
-    ParseIntError(#[from] std::num::ParseIntError),
+    InputError(#[from] InputError),
 }
-
-fn read_lines<P>(filename: P) -> Result<Vec<i32>, Day1Error>
-    where P: AsRef<Path>, {
-    parse_lines(read_lines_buf(filename)?)
-}
-
-fn parse_lines<F>(lines: io::Lines<io::BufReader<File>>) -> Result<Vec<F>, Day1Error>
-    where
-        F: FromStr,
-        Day1Error: From<<F as FromStr>::Err>, {
-    lines.map(|line| Ok(line?.parse()?)).collect()
-}
-
-fn read_lines_buf<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-    where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-
-    Ok(io::BufReader::new(file).lines())
-}
\ No newline at end of file
@@ -1,34 +1,61 @@
-use std::fs::File;
-use std::io;
-use std::io::BufRead;
-use std::path::Path;
+//! Day 2: Dive!
+
 use std::str::FromStr;
 
 use clap::Parser;
 use thiserror::Error;
 
+use crate::{config, mem};
+use crate::input::{self, Input, InputError};
+use crate::answer;
+
 #[derive(Parser)]
 pub struct Day2SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 pub fn main(args: Day2SubCmd) -> Result<(), Day2Error> {
-    let commands = read_lines(args.input_filename)?;
-    let pos = commands.iter().fold(Position::default(), |p, c| p.execute_command(c));
-    println!("Resulting position {:?}, multiply {}", pos, pos.horizontal * pos.depth);
+    let commands = mem::phase("parse", || parse(&config::input(2, args.input_filename)?))?;
+    let pos = mem::phase("solve", || navigate(&commands));
+    answer!("Resulting position {:?}, multiply {}", pos, pos.horizontal * pos.depth);
 
     Ok(())
 }
 
+/// Submarine commands, one `<direction> <magnitude>` per line.
+pub fn parse(input: &Input) -> Result<Vec<Command>, Day2Error> {
+    Ok(input::parse_lines(input)?)
+}
+
+/// Horizontal position multiplied by depth when up and down change the depth directly.
+///
+/// Without aim the depth is what the aim accumulates, so the same navigation serves both parts.
+pub fn part1(commands: &[Command]) -> i32 {
+    let pos = navigate(commands);
+    pos.horizontal * pos.aim
+}
+
+/// Horizontal position multiplied by depth when up and down change the aim.
+pub fn part2(commands: &[Command]) -> i32 {
+    let pos = navigate(commands);
+    pos.horizontal * pos.depth
+}
+
+/// Executes all commands starting from the surface.
+pub fn navigate(commands: &[Command]) -> Position {
+    commands.iter().fold(Position::default(), |p, c| p.execute_command(c))
+}
+
 #[derive(Debug, Default)]
-struct Position {
-    horizontal: i32,
-    depth: i32,
-    aim: i32,
+pub struct Position {
+    pub horizontal: i32,
+    pub depth: i32,
+    pub aim: i32,
 }
 
 impl Position {
-    fn execute_command(&self, cmd: &Command) -> Position {
+    pub fn execute_command(&self, cmd: &Command) -> Position {
         match cmd.direction {
             Direction::Forward =>
                 Position {
@@ -53,7 +80,7 @@ impl Position {
 }
 
 #[derive(Debug)]
-enum Direction {
+pub enum Direction {
     Forward,
     Up,
     Down,
@@ -86,9 +113,9 @@ pub enum ParseCommandError {
 }
 
 #[derive(Debug)]
-struct Command {
-    direction: Direction,
-    magnitude: i32,
+pub struct Command {
+    pub direction: Direction,
+    pub magnitude: i32,
 }
 
 impl FromStr for Command {
@@ -110,26 +137,5 @@ impl FromStr for Command {
 #[derive(Error, Debug)]
 pub enum Day2Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
-    #[error(transparent)]
-    ParseCommandError(#[from] ParseCommandError),
-}
-
-fn read_lines<P>(filename: P) -> Result<Vec<Command>, Day2Error>
-    where P: AsRef<Path>, {
-    parse_lines(read_lines_buf(filename)?)
+    InputError(#[from] InputError),
 }
-
-fn parse_lines<F>(lines: io::Lines<io::BufReader<File>>) -> Result<Vec<F>, Day2Error>
-    where
-        F: FromStr,
-        Day2Error: From<<F as FromStr>::Err>, {
-    lines.map(|line| Ok(line?.parse()?)).collect()
-}
-
-fn read_lines_buf<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-    where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-
-    Ok(io::BufReader::new(file).lines())
-}
\ No newline at end of file
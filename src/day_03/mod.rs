@@ -1,5 +1,4 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+//! Day 3: Binary Diagnostic
 
 use bitvec::field::BitField;
 use bitvec::order::Msb0;
@@ -7,38 +6,93 @@ use bitvec::prelude::BitVec;
 use clap::Parser;
 use thiserror::Error;
 
+use crate::{config, mem};
+use crate::input::{self, Input, InputError};
+use crate::{answer, info};
+
 #[derive(Parser)]
 pub struct Day3SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 pub fn main(args: Day3SubCmd) -> Result<(), Day3Error> {
-    let bits = read_lines(args.input_filename.as_str())?;
+    let bits = mem::phase("parse", || parse(&config::input(3, args.input_filename)?))?;
 
-    let gamma: BitVec<Msb0> = bits.iter().map(|b| b.count_ones() > b.len() / 2).collect();
-    let epsilon: BitVec<Msb0> = !gamma.clone();
+    let (gamma, epsilon) = mem::phase("part 1", || {
+        let gamma = gamma_rate(&bits);
+        let epsilon: BitVec<Msb0> = !gamma.clone();
+        (gamma, epsilon)
+    });
 
     let gamma_v = gamma.load::<u32>();
     let epsilon_v = epsilon.load::<u32>();
 
-    println!("Gamma: {}, {}", gamma_v, gamma);
-    println!("Epsilon: {}, {}", epsilon_v, epsilon);
-    println!("Power consumption: {}", gamma_v * epsilon_v);
+    info!("Gamma: {}, {}", gamma_v, gamma);
+    info!("Epsilon: {}, {}", epsilon_v, epsilon);
+    answer!("Power consumption: {}", gamma_v * epsilon_v);
 
-    let oxygen_rating: BitVec<Msb0> = progressive_filter(&bits, true);
+    let (oxygen_rating, co2_rating): (BitVec<Msb0>, BitVec<Msb0>) =
+        mem::phase("part 2", || (progressive_filter(&bits, true), progressive_filter(&bits, false)));
     let oxygen_rating_v = oxygen_rating.load::<u32>();
-
-    let co2_rating: BitVec<Msb0> = progressive_filter(&bits, false);
     let co2_rating_v = co2_rating.load::<u32>();
 
-    println!("Oxygen rating: {}, {}", oxygen_rating_v, oxygen_rating);
-    println!("CO2 rating: {}, {}", co2_rating_v, co2_rating);
-    println!("Support rating: {}", oxygen_rating_v * co2_rating_v);
+    info!("Oxygen rating: {}, {}", oxygen_rating_v, oxygen_rating);
+    info!("CO2 rating: {}, {}", co2_rating_v, co2_rating);
+    answer!("Support rating: {}", oxygen_rating_v * co2_rating_v);
 
     Ok(())
 }
 
-fn progressive_filter(bits: &[BitVec<>], most_common: bool) -> BitVec<Msb0> {
+/// Diagnostic report transposed into columns: `bits[i]` holds the i-th bit of every number.
+pub fn parse(input: &Input) -> Result<Vec<BitVec>, Day3Error> {
+    let rows = input::parse_lines_by(input, parse_row)?;
+
+    let mut res: Vec<BitVec> = vec![];
+    for row in rows {
+        for (i, b) in row.into_iter().enumerate() {
+            if res.len() < i + 1 {
+                res.push(BitVec::new());
+            }
+
+            res[i].push(b);
+        }
+    }
+
+    Ok(res)
+}
+
+fn parse_row(line: &str) -> Result<Vec<bool>, Day3Error> {
+    line.chars()
+        .map(|ch| match ch {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(Day3Error::BinaryDigitError(ch)),
+        })
+        .collect()
+}
+
+/// Power consumption, gamma rate multiplied by epsilon rate.
+pub fn part1(bits: &[BitVec]) -> u32 {
+    let gamma = gamma_rate(bits);
+    let epsilon: BitVec<Msb0> = !gamma.clone();
+
+    gamma.load::<u32>() * epsilon.load::<u32>()
+}
+
+/// Life support rating, oxygen generator rating multiplied by CO2 scrubber rating.
+pub fn part2(bits: &[BitVec]) -> u32 {
+    progressive_filter(bits, true).load::<u32>() * progressive_filter(bits, false).load::<u32>()
+}
+
+/// Most common bit of every column.
+pub fn gamma_rate(bits: &[BitVec]) -> BitVec<Msb0> {
+    bits.iter().map(|b| b.count_ones() > b.len() / 2).collect()
+}
+
+/// Finds the number left after repeatedly keeping only the rows with the most (or least) common
+/// bit in each column.
+pub fn progressive_filter(bits: &[BitVec<>], most_common: bool) -> BitVec<Msb0> {
     let mut row_mask: BitVec<> = BitVec::repeat(true, bits[0].len());
 
     for col in bits.iter() {
@@ -66,32 +120,8 @@ fn progressive_filter(bits: &[BitVec<>], most_common: bool) -> BitVec<Msb0> {
 #[derive(Error, Debug)]
 pub enum Day3Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
-}
+    InputError(#[from] InputError),
 
-fn read_lines(filename: &str) -> Result<Vec<BitVec>, Day3Error> {
-    parse_lines(read_lines_buf(filename)?)
+    #[error("Expected a binary digit, got {0:?}")]
+    BinaryDigitError(char),
 }
-
-fn parse_lines(lines: io::Lines<io::BufReader<File>>) -> Result<Vec<BitVec>, Day3Error> {
-    let mut res: Vec<BitVec> = vec![];
-
-    for line in lines {
-        for (i, ch) in line?.char_indices() {
-            let b: bool = ch == '1';
-            if res.len() < i + 1 {
-                res.push(BitVec::new());
-            }
-
-            res[i].push(b);
-        }
-    }
-
-    Ok(res)
-}
-
-fn read_lines_buf(filename: &str) -> io::Result<io::Lines<io::BufReader<File>>> {
-    let file = File::open(filename)?;
-
-    Ok(io::BufReader::new(file).lines())
-}
\ No newline at end of file
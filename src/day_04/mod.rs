@@ -1,50 +1,118 @@
-use std::fs::File;
-use std::io::{self, Read};
+//! Day 4: Giant Squid
+
 use std::str::FromStr;
 
 use clap::Parser;
 use thiserror::Error;
 
-const BOARD_SIZE: usize = 5;
+use crate::{config, mem};
+use crate::input::{self, Input, InputError, ParseError, Records};
+use crate::{answer, debug, info, trace};
+
+pub const BOARD_SIZE: usize = 5;
 
 #[derive(Parser)]
 pub struct Day4SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 pub fn main(args: Day4SubCmd) -> Result<(), Day4Error> {
-    let (guesses, mut boards) = read_lines(args.input_filename.as_str())?;
-    println!("Guesses: {:?}", guesses);
+    let (guesses, boards) = mem::phase("parse", || parse(&config::input(4, args.input_filename)?))?;
+    debug!("Guesses: {:?}", guesses);
     for (i, b) in boards.iter().enumerate() {
-        println!("Board {}:\n{:?}", i, b);
+        trace!("Board {}:\n{:?}", i, b);
     }
 
+    let wins = mem::phase("solve", || play(&guesses, &boards));
+    for win in wins.iter() {
+        debug!("Winning board: {:?}", win.board);
+        info!("Sum unmarked: {}, Winning number: {}, Score: {}", win.sum_unmarked, win.number, win.score());
+    }
+
+    let (first, last) = wins.first().zip(wins.last()).ok_or(Day4Error::NoWinner)?;
+    answer!("First winning board score: {}", first.score());
+    answer!("Last winning board score: {}", last.score());
+
+    Ok(())
+}
+
+/// Drawn numbers from the first line followed by boards, each preceded by a blank line.
+pub fn parse(input: &Input) -> Result<(Guesses, Vec<BingoBoard>), Day4Error> {
+    let blocks = input::blocks(input)?;
+    let (first, rest) = blocks.split_first()
+        .ok_or_else(|| ParseError::new(input, 1, "", "Expected the drawn numbers, got an empty input"))?;
+
+    if first.lines.len() > 1 {
+        return Err(ParseError::new(input, first.line + 1, &first.lines[1], "Expected a blank line after the drawn numbers").into());
+    }
+
+    let guesses: Guesses = first.lines[0].parse()
+        .map_err(|e| ParseError::new(input, first.line, &first.lines[0], e))?;
+    let mut boards = Records::new(input);
+
+    for block in rest {
+        let rows: Vec<&str> = block.lines.iter().map(String::as_str).collect();
+        boards.push(BingoBoard::from_lines(&rows)
+            .map_err(|e| ParseError::new(input, block.line, rows[0], e)));
+    }
+
+    Ok((guesses, boards.finish()?))
+}
+
+/// Score of the board which wins first.
+pub fn part1(guesses: &Guesses, boards: &[BingoBoard]) -> Option<usize> {
+    play(guesses, boards).first().map(|w| w.score())
+}
+
+/// Score of the board which wins last.
+pub fn part2(guesses: &Guesses, boards: &[BingoBoard]) -> Option<usize> {
+    play(guesses, boards).last().map(|w| w.score())
+}
+
+/// Board completing a row or a column, captured at the moment of winning.
+#[derive(Debug)]
+pub struct Win {
+    pub index: usize,
+    pub board: BingoBoard,
+    pub number: usize,
+    pub sum_unmarked: usize,
+}
+
+impl Win {
+    pub fn score(&self) -> usize {
+        self.sum_unmarked * self.number
+    }
+}
+
+/// Draws the numbers one by one and returns the boards in the order they win.
+pub fn play(guesses: &Guesses, boards: &[BingoBoard]) -> Vec<Win> {
+    let mut boards = boards.to_vec();
     let mut winning_boards: Vec<bool> = vec![false; boards.len()];
+    let mut wins = vec![];
 
-    for guess in guesses.guesses {
+    for &guess in guesses.guesses.iter() {
         for (i, b) in boards.iter_mut().enumerate() {
             b.mark_number(guess);
 
             if b.is_winning() && !winning_boards[i] {
                 winning_boards[i] = true;
-                println!("Winning board: {:?}", b);
-                let sum_unmarked = b.sum_unmarked();
-                println!("Sum unmarked: {}, Winning number: {}, Score: {}", sum_unmarked, guess, sum_unmarked * guess);
+                wins.push(Win { index: i, board: b.clone(), number: guess, sum_unmarked: b.sum_unmarked() });
             }
         }
     }
 
-    Ok(())
+    wins
 }
 
-#[derive(Default, Debug)]
-struct BingoBoard {
+#[derive(Default, Debug, Clone)]
+pub struct BingoBoard {
     board: [[usize; BOARD_SIZE]; BOARD_SIZE],
     marks: [[bool; BOARD_SIZE]; BOARD_SIZE],
 }
 
 impl BingoBoard {
-    fn mark_number(&mut self, num: usize) {
+    pub fn mark_number(&mut self, num: usize) {
         for i in 0..BOARD_SIZE {
             for j in 0..BOARD_SIZE {
                 if self.board[i][j] == num {
@@ -54,7 +122,7 @@ impl BingoBoard {
         }
     }
 
-    fn sum_unmarked(&self) -> usize {
+    pub fn sum_unmarked(&self) -> usize {
         let mut sum = 0;
         for i in 0..BOARD_SIZE {
             for j in 0..BOARD_SIZE {
@@ -67,7 +135,7 @@ impl BingoBoard {
         sum
     }
 
-    fn is_winning(&self) -> bool {
+    pub fn is_winning(&self) -> bool {
         for i in 0..BOARD_SIZE {
             let mut is_winning_col = true;
             let mut is_winning_row = true;
@@ -104,7 +172,7 @@ pub enum ParseBingoBoardError {
 
 impl BingoBoard {
     #[allow(clippy::comparison_chain)]
-    fn from_lines(lines: &[&str]) -> Result<Self, ParseBingoBoardError> {
+    pub fn from_lines(lines: &[&str]) -> Result<Self, ParseBingoBoardError> {
         if lines.len() < BOARD_SIZE { return Err(ParseBingoBoardError::TooFewRowsError); } else if lines.len() > BOARD_SIZE { return Err(ParseBingoBoardError::TooManyRowsError); }
 
         let mut board = BingoBoard::default();
@@ -122,8 +190,8 @@ impl BingoBoard {
 }
 
 #[derive(Debug)]
-struct Guesses {
-    guesses: Vec<usize>,
+pub struct Guesses {
+    pub guesses: Vec<usize>,
 }
 
 impl FromStr for Guesses {
@@ -143,35 +211,14 @@ impl FromStr for Guesses {
 #[derive(Error, Debug)]
 pub enum Day4Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
-
-    #[error(transparent)]
-    ParseBingoBoardError(#[from] ParseBingoBoardError),
+    InputError(#[from] InputError),
 
-    #[error("Unable to parse the number")]
-    ParseIntError(#[from] std::num::ParseIntError),
-
-    #[error("File is incorrectly     formatted")]
-    FileParsingError,
+    #[error("No board wins with the drawn numbers")]
+    NoWinner,
 }
 
-fn read_lines(filename: &str) -> Result<(Guesses, Vec<BingoBoard>), Day4Error> {
-    let mut lines: String = String::new();
-    File::open(filename)?.read_to_string(&mut lines)?;
-    let lines: Vec<&str> = lines.lines().collect();
-
-    if lines.len() < 2 {
-        return Err(Day4Error::FileParsingError);
+impl From<ParseError> for Day4Error {
+    fn from(e: ParseError) -> Self {
+        Day4Error::InputError(e.into())
     }
-
-    let guesses: Guesses = lines[0].parse()?;
-    let mut boards: Vec<BingoBoard> = vec![];
-
-    // skip separator line, assume each board has separator at the end as well
-    for chunks in lines[1..].chunks(BOARD_SIZE + 1) {
-        let chunks = &chunks[1..];
-        boards.push(BingoBoard::from_lines(chunks)?);
-    }
-
-    Ok((guesses, boards))
 }
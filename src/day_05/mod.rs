@@ -1,6 +1,6 @@
+//! Day 5: Hydrothermal Venture
+
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, BufRead};
 use std::iter;
 use std::str::FromStr;
 
@@ -8,17 +8,21 @@ use clap::Parser;
 use thiserror::Error;
 
 use crate::day_05::Day5Error::{LineParsingError, PointParsingError};
+use crate::{cancel, config, mem};
+use crate::input::{self, Input, InputError};
+use crate::{answer, debug, trace};
 
 #[derive(Parser)]
 pub struct Day5SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum Day5Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
+    InputError(#[from] InputError),
 
     #[error("Unable to parse the number")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -31,27 +35,55 @@ pub enum Day5Error {
 }
 
 pub fn main(args: Day5SubCmd) -> Result<(), Day5Error> {
-    let lines = read_lines(&args.input_filename)?;
+    let lines = mem::phase("parse", || parse(&config::input(5, args.input_filename)?))?;
+
+    let intersections = mem::phase("solve", || intersections(&lines));
+    answer!("Intersection points: {:?}", intersections.len());
+
+    Ok(())
+}
 
+/// Vent lines, one `x1,y1 -> x2,y2` per line.
+pub fn parse(input: &Input) -> Result<Vec<Line>, Day5Error> {
+    Ok(input::parse_lines(input)?)
+}
+
+/// Number of points where at least two horizontal or vertical lines overlap.
+pub fn part1(lines: &[Line]) -> usize {
+    let straight: Vec<Line> = lines.iter()
+        .filter(|l| l.is_horizontal() || l.is_vertical())
+        .copied()
+        .collect();
+
+    intersections(&straight).len()
+}
+
+/// Number of points where at least two lines overlap, diagonal ones included.
+pub fn part2(lines: &[Line]) -> usize {
+    intersections(lines).len()
+}
+
+/// All points covered by more than one line.
+pub fn intersections(lines: &[Line]) -> HashSet<Point> {
     let mut intersections: HashSet<Point> = HashSet::new();
 
     for (i, l1) in lines.iter().enumerate() {
+        cancel::checkpoint();
         for l2 in lines[i+1..].iter() {
             for p in l1.points().intersection(&l2.points()) {
-                // println!("{:?} & {:?} = {:?}", l1, l2, p);
-                intersections.insert(p.clone());
+                trace!("{:?} & {:?} = {:?}", l1, l2, p);
+                intersections.insert(*p);
             }
         }
     }
-    println!("Intersection points: {:?}", intersections.len());
 
-    Ok(())
+    intersections
 }
 
-#[derive(Hash, Eq, PartialEq, Debug, Clone)]
-struct Point {
-    x: i32,
-    y: i32
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32
 }
 
 impl FromStr for Point {
@@ -65,10 +97,10 @@ impl FromStr for Point {
     }
 }
 
-#[derive(Debug)]
-struct Line {
-    start: Point,
-    end: Point
+#[derive(Debug, Clone, Copy)]
+pub struct Line {
+    pub start: Point,
+    pub end: Point
 }
 
 fn directional_range(a: i32, b: i32) -> Box<dyn Iterator<Item = i32>> {
@@ -80,7 +112,8 @@ fn directional_range(a: i32, b: i32) -> Box<dyn Iterator<Item = i32>> {
 }
 
 impl Line {
-    fn points(&self) -> HashSet<Point> {
+    /// Points covered by a horizontal, vertical or 45 degree diagonal line, empty for any other.
+    pub fn points(&self) -> HashSet<Point> {
         let xs = directional_range(self.start.x, self.end.x);
         let ys = directional_range(self.start.y, self.end.y);
 
@@ -100,20 +133,20 @@ impl Line {
                 .map(|(x, y)| Point { x, y })
                 .collect()
         } else {
-            println!("Non-diagonal line: {:?}", self);
+            debug!("Non-diagonal line: {:?}", self);
             HashSet::new()
         }
     }
 
-    fn is_vertical(&self) -> bool {
+    pub fn is_vertical(&self) -> bool {
         self.start.x == self.end.x
     }
 
-    fn is_horizontal(&self) -> bool {
+    pub fn is_horizontal(&self) -> bool {
         self.start.y == self.end.y
     }
 
-    fn is_diagonal(&self) -> bool {
+    pub fn is_diagonal(&self) -> bool {
         (self.start.x - self.end.x).abs() == (self.start.y - self.end.y).abs()
     }
 }
@@ -128,15 +161,3 @@ impl FromStr for Line {
         Ok(Line { start, end })
     }
 }
-
-fn read_lines(filename: &str) -> Result<Vec<Line>, Day5Error> {
-    let file = File::open(filename)?;
-
-    let lines = io::BufReader::new(file)
-        .lines()
-        .map(|l| l?.parse())
-        .flatten()
-        .collect();
-
-    Ok(lines)
-}
@@ -1,64 +1,153 @@
+//! Day 6: Lanternfish
+
 use std::collections::HashMap;
-use std::fs::File;
-use std::io;
-use std::io::Read;
 
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-type Fish = i64;
+use crate::{cancel, config, mem};
+use crate::input::{self, Input, InputError};
+use crate::observer::{NoObserver, Observer};
+use crate::{answer, debug};
+
+pub type Fish = i64;
 
-const FISH_BIRTH_DAYS: i64 = 8;
-const FISH_SPAWN_DAYS: i64 = 6;
+pub const FISH_BIRTH_DAYS: i64 = 8;
+pub const FISH_SPAWN_DAYS: i64 = 6;
 
 #[derive(Parser)]
 pub struct Day6SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
+}
+
+/// Parameters of the `[day6]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Days simulated by part 1
+    pub part1_days: Fish,
+    /// Days simulated by part 2
+    pub part2_days: Fish,
+    /// Days between two births of a fish
+    pub spawn_days: Fish,
+    /// Days before a newborn fish gives its first birth
+    pub birth_days: Fish,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { part1_days: 80, part2_days: 256, spawn_days: FISH_SPAWN_DAYS, birth_days: FISH_BIRTH_DAYS }
+    }
+}
+
+impl Config {
+    /**
+    Rejects negative settings, and days after which the number of fish could overflow. The fish
+    at most double every `min(spawn_days, birth_days) + 1` days, 50 doublings leave room for a
+    couple thousand fish at the start.
+     **/
+    pub fn check(&self) -> Result<(), String> {
+        for (name, days) in [("spawn_days", self.spawn_days), ("birth_days", self.birth_days)] {
+            if days < 0 {
+                return Err(format!("Expected day6.{} to be at least 0, got {}", name, days));
+            }
+        }
+
+        let max_days = self.spawn_days.min(self.birth_days).saturating_add(1).saturating_mul(50);
+        for (name, days) in [("part1_days", self.part1_days), ("part2_days", self.part2_days)] {
+            if !(0..=max_days).contains(&days) {
+                return Err(format!("Expected day6.{} between 0 and {} with these spawn_days and birth_days, got {}", name, max_days, days));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum Day6Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
+    InputError(#[from] InputError),
+}
 
-    #[error("Unable to parse the number")]
-    ParseIntError(#[from] std::num::ParseIntError),
+/// Population at the end of a simulated day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FishDay {
+    pub day: Fish,
+    pub total: Fish,
 }
 
 pub fn main(args: Day6SubCmd) -> Result<(), Day6Error> {
-    let mut genesis_fishes = String::new();
-    File::open(args.input_filename)?.read_to_string(&mut genesis_fishes)?;
+    let genesis_fishes = mem::phase("parse", || parse(&config::input(6, args.input_filename)?))?;
+    debug!("Initial state: {:?}", genesis_fishes);
+
+    let config = &config::get().day6;
+    mem::phase("solve", || simulate(&genesis_fishes, config.part1_days.max(config.part2_days), config, &mut |e: &FishDay| {
+        if e.day == config.part1_days || e.day == config.part2_days {
+            answer!("Total fishes spawned on Day {}: {}", e.day, e.total);
+        } else {
+            debug!("Total fishes spawned on Day {}: {}", e.day, e.total);
+        }
+    }));
 
-    let genesis_fishes: Vec<Fish> = genesis_fishes.split(',').map(|d| d.parse()).flatten().collect();
-    println!("Initial state: {:?}", genesis_fishes);
+    Ok(())
+}
 
+/// Comma-separated timers of the initial fish.
+pub fn parse(input: &Input) -> Result<Vec<Fish>, Day6Error> {
+    Ok(input::parse_list(input, ',')?)
+}
+
+/// Number of fish after 80 days, or as many as configured.
+pub fn part1(genesis_fishes: &[Fish], config: &Config) -> Fish {
+    simulate(genesis_fishes, config.part1_days, config, &mut NoObserver)
+}
+
+/// Number of fish after 256 days, or as many as configured.
+pub fn part2(genesis_fishes: &[Fish], config: &Config) -> Fish {
+    simulate(genesis_fishes, config.part2_days, config, &mut NoObserver)
+}
+
+/// Number of fish after `days`, reporting the population at the end of every day.
+pub fn simulate<O: Observer<FishDay>>(genesis_fishes: &[Fish], days: Fish, config: &Config, observer: &mut O) -> Fish {
     let mut memo = HashMap::new();
+    let mut total = genesis_fishes.len() as Fish;
 
-    for days in 1..=256 {
-        let mut total_spawn: Fish = genesis_fishes.len() as Fish;
-        for &f in genesis_fishes.iter() {
-            total_spawn += fishes_generation(f, days, &mut memo);
-        }
+    for day in 1..=days {
+        total = total_fishes(genesis_fishes, day, config, &mut memo);
+        observer.observe(&FishDay { day, total });
+    }
+
+    total
+}
 
-        println!("Total fishes spawned on Day {}: {}", days, total_spawn);
+/// Initial fish together with everything they spawn within `days`.
+pub fn total_fishes(genesis_fishes: &[Fish], days: Fish, config: &Config, memo: &mut HashMap<(Fish, Fish), Fish>) -> Fish {
+    let mut total_spawn: Fish = genesis_fishes.len() as Fish;
+    for &f in genesis_fishes.iter() {
+        total_spawn += fishes_generation(f, days, config, memo);
     }
 
-    Ok(())
+    total_spawn
 }
 
-fn fishes_generation(age: Fish, days_left: Fish, memo: &mut HashMap<(Fish, Fish), Fish>) -> Fish {
+/// Number of descendants a fish with timer `age` spawns within `days_left`, memoised by both.
+pub fn fishes_generation(age: Fish, days_left: Fish, config: &Config, memo: &mut HashMap<(Fish, Fish), Fish>) -> Fish {
     if age >= days_left { return 0; }
     if let Some(&result) = memo.get(&(age, days_left)) { return result; }
+    cancel::checkpoint();
 
     let lifetime = days_left - age - 1;
-    let siblings = lifetime / (FISH_SPAWN_DAYS + 1);
+    let siblings = lifetime / (config.spawn_days + 1);
     // firstborn accounted separately
     let family = 1 + siblings;
 
     let mut generation = family;
     for i in 0..family {
-        let sibling_age = lifetime - i * (FISH_SPAWN_DAYS + 1);
-        generation += fishes_generation(FISH_BIRTH_DAYS, sibling_age, memo);
+        let sibling_age = lifetime - i * (config.spawn_days + 1);
+        generation += fishes_generation(config.birth_days, sibling_age, config, memo);
     }
 
     memo.insert((age, days_left), generation);
@@ -68,27 +157,28 @@ fn fishes_generation(age: Fish, days_left: Fish, memo: &mut HashMap<(Fish, Fish)
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::observer::Recorder;
 
     #[test]
     fn test_zero_days() {
         // no spawn
-        assert_eq!(fishes_generation(0, 0, &mut HashMap::new()), 0);
-        assert_eq!(fishes_generation(1, 0, &mut HashMap::new()), 0);
+        assert_eq!(fishes_generation(0, 0, &Config::default(), &mut HashMap::new()), 0);
+        assert_eq!(fishes_generation(1, 0, &Config::default(), &mut HashMap::new()), 0);
     }
 
     #[test]
     fn test_first_day() {
         // i: 1
         // 1: 0
-        assert_eq!(fishes_generation(1, 1, &mut HashMap::new()), 0);
+        assert_eq!(fishes_generation(1, 1, &Config::default(), &mut HashMap::new()), 0);
 
         // i: 2
         // 1: 1
-        assert_eq!(fishes_generation(2, 1, &mut HashMap::new()), 0);
+        assert_eq!(fishes_generation(2, 1, &Config::default(), &mut HashMap::new()), 0);
 
         // i: 0
         // 1: 6, 8
-        assert_eq!(fishes_generation(0, 1, &mut HashMap::new()), 1);
+        assert_eq!(fishes_generation(0, 1, &Config::default(), &mut HashMap::new()), 1);
     }
 
     #[test]
@@ -96,13 +186,13 @@ mod tests {
         // i: 1
         // 1: 0
         // 2: 6, 8
-        assert_eq!(fishes_generation(1, 2, &mut HashMap::new()), 1);
+        assert_eq!(fishes_generation(1, 2, &Config::default(), &mut HashMap::new()), 1);
 
         // i: 2
         // 1: 1
         // 2: 0
         // 3: 6, 8
-        assert_eq!(fishes_generation(2, 3, &mut HashMap::new()), 1);
+        assert_eq!(fishes_generation(2, 3, &Config::default(), &mut HashMap::new()), 1);
 
         // i: 3
         // 1: 2
@@ -110,7 +200,7 @@ mod tests {
         // 3: 0
         // 4: 6, 8
         // 5: 5, 7
-        assert_eq!(fishes_generation(3, 5, &mut HashMap::new()), 1);
+        assert_eq!(fishes_generation(3, 5, &Config::default(), &mut HashMap::new()), 1);
 
         // i: 6
         // 1: 5
@@ -121,7 +211,7 @@ mod tests {
         // 6: 0
         // 7: 6, 8
         // 8: 5, 7
-        assert_eq!(fishes_generation(6, 8, &mut HashMap::new()), 1);
+        assert_eq!(fishes_generation(6, 8, &Config::default(), &mut HashMap::new()), 1);
 
         // i: 8
         // 1: 7
@@ -133,7 +223,7 @@ mod tests {
         // 7: 1
         // 8: 0, 8
         // 9: 6, 7
-        assert_eq!(fishes_generation(8, 9, &mut HashMap::new()), 1);
+        assert_eq!(fishes_generation(8, 9, &Config::default(), &mut HashMap::new()), 1);
     }
 
     #[test]
@@ -148,7 +238,7 @@ mod tests {
         // 7: 0, 2
         // 8: 6, 1, 8
         // 9: 5, 0, 7
-        assert_eq!(fishes_generation(0, 9, &mut HashMap::new()), 2);
+        assert_eq!(fishes_generation(0, 9, &Config::default(), &mut HashMap::new()), 2);
 
         //  i: 3
         //  1: 2
@@ -169,7 +259,7 @@ mod tests {
         // 16: 1, 3, 3, 5
         // 17: 0, 2, 2, 4
         // 18: 6, 1, 1, 3, 8
-        assert_eq!(fishes_generation(3, 18, &mut HashMap::new()), 4);
+        assert_eq!(fishes_generation(3, 18, &Config::default(), &mut HashMap::new()), 4);
 
         //  i: 0
         //  1: 6, 8
@@ -182,7 +272,7 @@ mod tests {
         //  8: 6, 1, 8
         //  9: 5, 0, 7
         // 10: 4, 6, 6, 8
-        assert_eq!(fishes_generation(0, 10, &mut HashMap::new()), 3);
+        assert_eq!(fishes_generation(0, 10, &Config::default(), &mut HashMap::new()), 3);
     }
 
     #[test]
@@ -205,7 +295,7 @@ mod tests {
         // 15: 6, 1, 1, 3, 8
         // 16: 5, 0, 0, 2, 8
         // 17: 4, 6, 6, 1, 7, 8, 8
-        assert_eq!(fishes_generation(0, 17, &mut HashMap::new()), 6);
+        assert_eq!(fishes_generation(0, 17, &Config::default(), &mut HashMap::new()), 6);
     }
 
     #[test]
@@ -230,6 +320,16 @@ mod tests {
         // 17: 4, 6, 6, 1, 7, 8, 8
         // 18: 3, 5, 5, 0, 6, 7, 7
         // 19: 2, 4, 4, 6, 5, 6, 6, 8
-        assert_eq!(fishes_generation(0, 19, &mut HashMap::new()), 7);
+        assert_eq!(fishes_generation(0, 19, &Config::default(), &mut HashMap::new()), 7);
+    }
+
+    #[test]
+    fn test_simulate_reports_every_day() {
+        let mut recorder = Recorder::new();
+        assert_eq!(simulate(&[3, 4, 3, 1, 2], 18, &Config::default(), &mut recorder), 26);
+
+        let totals: Vec<Fish> = recorder.events.iter().map(|e| e.total).collect();
+        assert_eq!(totals[..3], [5, 6, 7]);
+        assert_eq!(recorder.events.last(), Some(&FishDay { day: 18, total: 26 }));
     }
 }
@@ -1,38 +1,39 @@
+//! Day 7: The Treachery of Whales
+
 // use std::cmp::Ordering;
-use std::fs::File;
-use std::io;
-use std::io::Read;
 use std::ops::Range;
 // use bitvec::macros::internal::funty::{IsInteger, IsNumber, IsSigned};
 
 use clap::Parser;
 use thiserror::Error;
 
+use crate::{config, mem};
+use crate::input::{self, Input, InputError};
+use crate::{answer, debug};
+
 #[derive(Parser)]
 pub struct Day7SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[derive(Error, Debug)]
 pub enum Day7Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
-
-    #[error("Unable to parse the number")]
-    ParseIntError(#[from] std::num::ParseIntError),
+    InputError(#[from] InputError),
 }
 
 pub fn main(args: Day7SubCmd) -> Result<(), Day7Error> {
-    let positions = read_positions(args.input_filename.as_str())?;
-    println!("Crabs horizontal positions: {:?}", positions);
+    let positions = mem::phase("parse", || parse(&config::input(7, args.input_filename)?))?;
+    debug!("Crabs horizontal positions: {:?}", positions);
 
-    let r = *positions.iter().min().unwrap() ..*positions.iter().max().unwrap();
+    let r = positions_range(&positions);
 
-    let (naive_min, naive_min_d) = find_min(&r, |x| distance(&positions, x));
-    println!("Naive crab position: {}, distance: {}", naive_min, naive_min_d);
+    let (naive_min, naive_min_d) = mem::phase("part 1", || find_min(&r, |x| distance(&positions, x)));
+    answer!("Naive crab position: {}, distance: {}", naive_min, naive_min_d);
 
-    let (factored_min, factored_min_d) = find_min(&r, |x| factor_distance(&positions, x));
-    println!("Factored crab position: {}, factored distance: {}", factored_min, factored_min_d);
+    let (factored_min, factored_min_d) = mem::phase("part 2", || find_min(&r, |x| factor_distance(&positions, x)));
+    answer!("Factored crab position: {}, factored distance: {}", factored_min, factored_min_d);
 
     // let b = bisect(&r, |l, r| distance(&positions, l).cmp(&distance(&positions, r)));
     // println!("Bisected position: {}, distance: {}", b, distance(&positions, b));
@@ -62,29 +63,38 @@ pub fn main(args: Day7SubCmd) -> Result<(), Day7Error> {
     Ok(())
 }
 
-fn read_positions(filename: &str) -> Result<Vec<i32>, Day7Error> {
-    let mut input = String::new();
-    File::open(filename)?.read_to_string(&mut input)?;
+/// Comma-separated horizontal positions of the crabs.
+pub fn parse(input: &Input) -> Result<Vec<i32>, Day7Error> {
+    Ok(input::parse_list(input, ',')?)
+}
 
-    let mut res = vec![];
+/// Least fuel needed to align when every step costs one.
+pub fn part1(positions: &[i32]) -> i32 {
+    find_min(&positions_range(positions), |x| distance(positions, x)).1
+}
 
-    // Do not swallow possible errors
-    for s in input.split(',') {
-        res.push(s.trim().parse()?);
-    }
+/// Least fuel needed to align when every next step costs one more than the previous.
+pub fn part2(positions: &[i32]) -> i32 {
+    find_min(&positions_range(positions), |x| factor_distance(positions, x)).1
+}
 
-    Ok(res)
+/// Positions from the leftmost to the rightmost crab, both included.
+pub fn positions_range(positions: &[i32]) -> Range<i32> {
+    *positions.iter().min().unwrap() ..*positions.iter().max().unwrap() + 1
 }
 
-fn distance(xs: &[i32], e: i32) -> i32 {
+/// Fuel to move every crab to `e` at a constant rate.
+pub fn distance(xs: &[i32], e: i32) -> i32 {
     xs.iter().map(|&x| (e - x).abs()).sum()
 }
 
-fn factor_distance(xs: &[i32], e: i32) -> i32 {
+/// Fuel to move every crab to `e` at an increasing rate.
+pub fn factor_distance(xs: &[i32], e: i32) -> i32 {
     xs.iter().map(|&x| (e - x).abs()).map(|d| d*(d+1)/2).sum()
 }
 
-fn find_min<F>(r: &Range<i32>, f: F) -> (i32, i32)
+/// Position within the range minimising `f`, along with the minimum.
+pub fn find_min<F>(r: &Range<i32>, f: F) -> (i32, i32)
     where F: Fn(i32) -> i32 {
     let mut min = r.start;
     let mut min_d = f(min);
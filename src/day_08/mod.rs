@@ -1,65 +1,98 @@
+//! Day 8: Seven Segment Search
+
 use std::collections::HashSet;
-use std::io;
 
 use clap::Parser as ClapParser;
 use thiserror::Error;
 
-type Signal = HashSet<Wire>;
+use crate::{cancel, config, mem, profile};
+use crate::input::{Input, InputError};
+use crate::{answer, debug, trace};
+
+pub type Signal = HashSet<Wire>;
 
 #[derive(ClapParser)]
 pub struct Day8SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum Day8Error {
     #[error(transparent)]
-    IOError(#[from] io::Error),
+    InputError(#[from] InputError),
 
     #[error("Unknown wire type")]
     ParseWireError,
 
-    #[error(transparent)]
-    ParsingError(#[from] nom::error::Error<String>),
+    #[error("No wiring matches entry {0}")]
+    NoCypher(usize),
 }
 
 pub fn main(args: Day8SubCmd) -> Result<(), Day8Error> {
-    let entries = reader::entries(args.input_filename.as_str())?;
+    let entries = mem::phase("parse", || parse(&config::input(8, args.input_filename)?))?;
 
     let mut known_digits = 0;
-    let permutations = permutations(&[0, 1, 2, 3, 4, 5, 6]);
-    println!("Total permutations: {}", permutations.len());
-    let cyphers = all_cyphers(permutations);
-    println!("Total cyphers: {}", cyphers.len());
-
     let mut sum_digits = 0;
 
-    for entry in entries.iter() {
-        println!("{:?}", entry);
+    mem::phase("solve", || -> Result<(), Day8Error> {
+        let permutations = permutations(&[0, 1, 2, 3, 4, 5, 6]);
+        debug!("Total permutations: {}", permutations.len());
+        let cyphers = all_cyphers(permutations);
+        debug!("Total cyphers: {}", cyphers.len());
+
+        for (i, entry) in entries.iter().enumerate() {
+            trace!("{:?}", entry);
 
-        if let Some(cypher) = find_cypher(entry, &cyphers) {
+            let cypher = find_cypher(entry, &cyphers).ok_or(Day8Error::NoCypher(i + 1))?;
             let decyphered = entry.decrypt(cypher);
             sum_digits += decyphered.output_num();
 
-            println!("{:?}", cypher);
-            println!("{:?}", decyphered);
-            println!("Signal: {}, Digits: {}", decyphered.signal_num(), decyphered.output_num());
-            println!();
+            trace!("{:?}", cypher);
+            trace!("{:?}", decyphered);
+            debug!("Signal: {}, Digits: {}", decyphered.signal_num(), decyphered.output_num());
+
+            known_digits += entry.outputs.iter().flat_map(signal_to_digit).count();
         }
 
-        known_digits += entry.outputs.iter().flat_map(signal_to_digit).count();
-    }
+        Ok(())
+    })?;
 
-    println!("Known simple digits counter: {}", known_digits);
-    println!("Sum of output digits: {}", sum_digits);
+    answer!("Known simple digits counter: {}", known_digits);
+    answer!("Sum of output digits: {}", sum_digits);
 
     Ok(())
 }
 
+/// Notes on the displays, one `<ten signals> | <four outputs>` entry per line.
+pub fn parse(input: &Input) -> Result<Vec<Entry>, Day8Error> {
+    reader::entries(input)
+}
+
+/// Number of times the digits with a unique segment count (1, 4, 7, 8) appear in the outputs.
+pub fn part1(entries: &[Entry]) -> usize {
+    entries.iter().map(|e| e.outputs.iter().flat_map(signal_to_digit).count()).sum()
+}
+
+/// Sum of all decoded output values, none when an entry can't be decoded.
+pub fn part2(entries: &[Entry]) -> Option<i64> {
+    let cyphers = all_cyphers(permutations(&[0, 1, 2, 3, 4, 5, 6]));
+
+    entries.iter()
+        .map(|e| decode(e, &cyphers).map(|d| d.output_num()))
+        .sum()
+}
+
+/// Decrypts the entry with the first cypher matching all of its signals.
+pub fn decode(entry: &Entry, cyphers: &[Cypher]) -> Option<DecryptedEntry> {
+    find_cypher(entry, cyphers).map(|c| entry.decrypt(c))
+}
+
+/// Signals of the digits 0 to 9, in that order, for one wiring of the segments.
 #[derive(Debug)]
-struct Cypher {
-    signals: Vec<Signal>,
+pub struct Cypher {
+    pub signals: Vec<Signal>,
 }
 
 impl Cypher {
@@ -81,7 +114,8 @@ impl Cypher {
 Implements Heap's algorithm https://en.wikipedia.org/wiki/Heap%27s_algorithm
 Using swaps without recursion to minimize number of moves
  **/
-fn permutations<T: Copy + Default>(items: &[T]) -> Vec<Vec<T>> {
+pub fn permutations<T: Copy + Default>(items: &[T]) -> Vec<Vec<T>> {
+    let _s = profile::span("permutations");
     let mut output = vec![];
     // stack is an encoding of the stack state
     // stack[k] encodes the for-loop counter for when recurse(i - 1, items) is called
@@ -119,7 +153,8 @@ fn permutations<T: Copy + Default>(items: &[T]) -> Vec<Vec<T>> {
 /**
 Generate all possible ways signal could be encrypted with wires mapping
  **/
-fn all_cyphers(permutations: Vec<Vec<usize>>) -> Vec<Cypher> {
+pub fn all_cyphers(permutations: Vec<Vec<usize>>) -> Vec<Cypher> {
+    let _s = profile::span("all_cyphers");
     fn signal(indices: &[usize], mapping: &[usize]) -> Signal {
         let mut signal: Signal = HashSet::new();
         let wires = Wire::all();
@@ -134,6 +169,7 @@ fn all_cyphers(permutations: Vec<Vec<usize>>) -> Vec<Cypher> {
     let mut cyphers = vec![];
 
     for mapping in permutations {
+        cancel::checkpoint();
         let signals = vec![
             signal(&[0, 1, 2, 4, 5, 6], &mapping),    // 0
             signal(&[2, 5], &mapping),                // 1
@@ -152,17 +188,14 @@ fn all_cyphers(permutations: Vec<Vec<usize>>) -> Vec<Cypher> {
     cyphers
 }
 
-fn find_cypher<'a>(entry: &Entry, cyphers: &'a [Cypher]) -> Option<&'a Cypher> {
-    for cypher in cyphers {
-        if entry.signals.iter().all(|s| cypher.signals.contains(s)) {
-            return Some(cypher);
-        }
-    }
-
-    None
+/// First cypher knowing every signal and output of the entry, so it can decrypt all of them.
+pub fn find_cypher<'a>(entry: &Entry, cyphers: &'a [Cypher]) -> Option<&'a Cypher> {
+    let _s = profile::span("find_cypher");
+    cyphers.iter().find(|cypher| entry.signals.iter().chain(entry.outputs.iter()).all(|s| cypher.signals.contains(s)))
 }
 
-fn signal_to_digit(signal: &Signal) -> Option<usize> {
+/// Digit for signals which can be recognised by their length alone.
+pub fn signal_to_digit(signal: &Signal) -> Option<usize> {
     match signal.len() {
         2 => Some(1),
         3 => Some(7),
@@ -173,7 +206,7 @@ fn signal_to_digit(signal: &Signal) -> Option<usize> {
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
-enum Wire {
+pub enum Wire {
     A,
     B,
     C,
@@ -184,7 +217,7 @@ enum Wire {
 }
 
 impl Wire {
-    fn all() -> [Wire; 7] {
+    pub fn all() -> [Wire; 7] {
         [
             Wire::A,
             Wire::B,
@@ -216,17 +249,18 @@ impl TryFrom<char> for Wire {
 
 #[derive(Default, Debug)]
 pub struct Entry {
-    signals: Vec<Signal>,
-    outputs: Vec<Signal>,
+    pub signals: Vec<Signal>,
+    pub outputs: Vec<Signal>,
 }
 
 #[derive(Debug)]
-struct DecryptedEntry {
-    signals: Vec<usize>,
-    outputs: Vec<usize>,
+pub struct DecryptedEntry {
+    pub signals: Vec<usize>,
+    pub outputs: Vec<usize>,
 }
 
-fn dec_vec_to_int(vec: &[usize]) -> i64 {
+/// Reads decimal digits as a number, most significant first.
+pub fn dec_vec_to_int(vec: &[usize]) -> i64 {
     let mut x = 0;
 
     for (i, &n) in vec.iter().enumerate() {
@@ -238,17 +272,17 @@ fn dec_vec_to_int(vec: &[usize]) -> i64 {
 }
 
 impl DecryptedEntry {
-    fn signal_num(&self) -> i64 {
+    pub fn signal_num(&self) -> i64 {
         dec_vec_to_int(&self.signals)
     }
 
-    fn output_num(&self) -> i64 {
+    pub fn output_num(&self) -> i64 {
         dec_vec_to_int(&self.outputs)
     }
 }
 
 impl Entry {
-    fn decrypt(&self, cypher: &Cypher) -> DecryptedEntry {
+    pub fn decrypt(&self, cypher: &Cypher) -> DecryptedEntry {
         DecryptedEntry {
             signals: cypher.decrypt(&self.signals),
             outputs: cypher.decrypt(&self.outputs),
@@ -257,28 +291,19 @@ impl Entry {
 }
 
 mod reader {
-    use std::fs::File;
-    use std::io;
-    use std::io::BufRead;
-
-    use nom::{Finish, IResult};
+    use nom::IResult;
     use nom::bytes::complete::tag;
     use nom::character::complete::{one_of, space1};
     use nom::combinator::{map, map_res};
     use nom::multi::{many1, separated_list0};
     use nom::sequence::separated_pair;
 
-    use super::{Day8Error, Entry, Signal, Wire};
-
-    pub fn entries(filename: &str) -> Result<Vec<Entry>, Day8Error> {
-        let mut entries = vec![];
+    use crate::input::{self, Input};
 
-        for line in io::BufReader::new(File::open(filename)?).lines() {
-            let (_, parsed) = entry(line?.as_str()).map_err(|e| e.to_owned()).finish()?;
-            entries.push(parsed);
-        }
+    use super::{Day8Error, Entry, Signal, Wire};
 
-        Ok(entries)
+    pub fn entries(input: &Input) -> Result<Vec<Entry>, Day8Error> {
+        Ok(input::parse_lines_with(input, entry)?)
     }
 
     fn entry(input: &str) -> IResult<&str, Entry> {
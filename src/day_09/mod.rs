@@ -1,12 +1,25 @@
-use clap::Parser;
+//! Day 9: Smoke Basin
+
+use clap::{ArgEnum, Parser};
 use thiserror::Error;
 
+use crate::input::Input;
+use crate::{config, mem, profile};
 use crate::matrix::{Matrix, MatrixError, Pos};
+use crate::{answer, debug, info, trace};
+
+#[derive(ArgEnum, Clone)]
+enum MatrixSize {
+    S5x10,
+    S100x100,
+}
 
 #[derive(Parser)]
 pub struct Day9SubCmd {
-    test_input_filename: String,
-    prod_input_filename: String,
+    #[clap(arg_enum)]
+    matrix_size: MatrixSize,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -17,41 +30,87 @@ pub enum Day9Error {
 }
 
 pub fn main(args: Day9SubCmd) -> Result<(), Day9Error> {
-    process_matrix::<5, 10>(args.test_input_filename.as_str())?;
-    println!();
-    process_matrix::<100, 100>(args.prod_input_filename.as_str())?;
+    let input = config::input(9, args.input_filename).map_err(MatrixError::from)?;
+
+    match args.matrix_size {
+        MatrixSize::S5x10 => process_matrix::<5, 10>(&input)?,
+        MatrixSize::S100x100 => process_matrix::<100, 100>(&input)?,
+    }
 
     Ok(())
 }
 
-fn process_matrix<const N: usize, const M: usize>(filename: &str) -> Result<(), Day9Error> {
-    let matrix: Matrix<u8, N, M> = Matrix::from_file(filename)?;
-    let minimas = find_local_minimas(&matrix);
-    println!("Local minimas: {:?}", minimas);
-    println!("Risk level for test matrix: {}", risk_level::<N, M>(&minimas, &matrix));
-
-    let mut cavern_sizes = vec![];
+fn process_matrix<const N: usize, const M: usize>(input: &Input) -> Result<(), Day9Error> {
+    let matrix: Matrix<u8, N, M> = mem::phase("parse", || parse(input))?;
+    let (minimas, risk) = mem::phase("part 1", || {
+        let minimas = find_local_minimas(&matrix);
+        let risk = risk_level::<N, M>(&minimas, &matrix);
+        (minimas, risk)
+    });
+    debug!("Local minimas: {:?}", minimas);
+    answer!("Risk level: {}", risk);
 
-    for pos in minimas {
-        let cavern = matrix.fill(&pos, |x| if *x != 9 { Some(*x) } else { None });
-        let cavern_size = cavern.find_indices(|f| f.is_some()).len();
-        cavern_sizes.push(cavern_size);
-        println!("Cavern of size: {} found", cavern_size);
+    let mut cavern_sizes = mem::phase("part 2", || basin_sizes(&matrix, &minimas));
+    for cavern_size in cavern_sizes.iter() {
+        trace!("Cavern of size: {} found", cavern_size);
     }
 
     cavern_sizes.sort();
     let three_basins = cavern_sizes[cavern_sizes.len() - 3..].to_vec();
-    println!("Three largest basins: {:?}", &three_basins);
-    println!("Basin metric: {}", three_basins.iter().fold(1, |acc, &y| acc * y));
+    info!("Three largest basins: {:?}", three_basins);
+    answer!("Basin metric: {}", three_basins.iter().product::<usize>());
 
     Ok(())
 }
 
-fn risk_level<const N: usize, const M: usize>(minima_coords: &[Pos<N, M>], matrix: &Matrix<u8, N, M>) -> u32 {
+/// Heightmap of `N` rows by `M` single-digit columns.
+pub fn parse<const N: usize, const M: usize>(input: &Input) -> Result<Matrix<u8, N, M>, Day9Error> {
+    Ok(Matrix::from_input(input)?)
+}
+
+/// Sum of risk levels of all low points.
+pub fn part1<const N: usize, const M: usize>(matrix: &Matrix<u8, N, M>) -> u32 {
+    risk_level(&find_local_minimas(matrix), matrix)
+}
+
+/// Product of the sizes of the three largest basins.
+pub fn part2<const N: usize, const M: usize>(matrix: &Matrix<u8, N, M>) -> usize {
+    let mut cavern_sizes = basin_sizes(matrix, &find_local_minimas(matrix));
+    cavern_sizes.sort();
+
+    cavern_sizes.iter().rev().take(3).product()
+}
+
+/// Size of the basin flowing into each of the low points, heights of 9 are not part of any basin.
+pub fn basin_sizes<const N: usize, const M: usize>(matrix: &Matrix<u8, N, M>, minimas: &[Pos<N, M>]) -> Vec<usize> {
+    minimas.iter()
+        .map(|pos| matrix.fill(pos, |x| if *x != 9 { Some(*x) } else { None }))
+        .map(|cavern| cavern.find_indices(|f| f.is_some()).len())
+        .collect()
+}
+
+/// Index of the low point each position flows into, none for the heights of 9.
+pub fn basins<const N: usize, const M: usize>(matrix: &Matrix<u8, N, M>) -> Matrix<Option<usize>, N, M> {
+    let _s = profile::span("basins");
+    let mut basins = Matrix::new(None);
+
+    for (index, minima) in find_local_minimas(matrix).iter().enumerate() {
+        let basin = matrix.fill(minima, |x| if *x != 9 { Some(index) } else { None });
+        for pos in basin.find_indices(|f| f.is_some()) {
+            basins.set(&pos, Some(index));
+        }
+    }
+
+    basins
+}
+
+/// Sum of heights plus one of the given positions.
+pub fn risk_level<const N: usize, const M: usize>(minima_coords: &[Pos<N, M>], matrix: &Matrix<u8, N, M>) -> u32 {
     minima_coords.iter().map(|pos| (*matrix.get(pos) as u32) + 1).sum()
 }
 
-fn find_local_minimas<T, const N: usize, const M: usize>(matrix: &Matrix<T, N, M>) -> Vec<Pos<N, M>>
+/// Positions lower than all of their direct neighbours.
+pub fn find_local_minimas<T, const N: usize, const M: usize>(matrix: &Matrix<T, N, M>) -> Vec<Pos<N, M>>
     where T: PartialOrd + Copy {
     fn is_local_minima<T: PartialOrd + Copy>(x: &T, neighbours: &[&T]) -> bool {
         neighbours.iter().all(|&n| n > x)
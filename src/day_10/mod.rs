@@ -1,73 +1,110 @@
+//! Day 10: Syntax Scoring
+
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io;
-use std::io::BufRead;
 
 use clap::Parser;
 use thiserror::Error;
 
+use crate::{config, mem};
+use crate::input::{self, Input, InputError};
+use crate::answer;
+
 #[derive(Parser)]
 pub struct Day10SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum Day10Error {
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
+    InputError(#[from] InputError),
+
+    #[error("Expected one of `()[]{{}}<>`, got {0:?}")]
+    UnknownBraceError(char),
 
     #[error("Incorrect brace sequence encountered")]
     BalancingError,
 }
 
 pub fn main(args: Day10SubCmd) -> Result<(), Day10Error> {
-    let lines = io::BufReader::new(File::open(args.input_filename)?).lines();
-
-    let mut score = 0;
-    let mut autocomplete_scores: Vec<i64> = vec![];
-
-    for line in lines {
-        let mut incomplete = false;
-        let mut balancer = Balancer::default();
-
-        for char in line?.chars() {
-            if balancer.process(char).is_err() {
-                incomplete = true;
-                match Brace::from(char) {
-                    Brace::Round => score += 3,
-                    Brace::Square => score += 57,
-                    Brace::Curly => score += 1197,
-                    Brace::Angled => score += 25137,
-                }
-            }
-        }
+    let lines = mem::phase("parse", || parse(&config::input(10, args.input_filename)?))?;
 
-        if !incomplete && !balancer.braces.is_empty() {
-            autocomplete_scores.push(balancer.braces.iter().rev().map(|b| match b {
-                Brace::Round => 1,
-                Brace::Square => 2,
-                Brace::Curly => 3,
-                Brace::Angled => 4,
-            }).fold(0, |acc, s| acc * 5 + s));
+    answer!("Total score of corrupted lines: {}", mem::phase("part 1", || part1(&lines)));
+    answer!("Middle score {}", mem::phase("part 2", || part2(&lines)));
+
+    Ok(())
+}
+
+/// Lines of the navigation subsystem, as is.
+pub fn parse(input: &Input) -> Result<Vec<String>, Day10Error> {
+    Ok(input::parse_lines_by(input, |line| {
+        match line.chars().find(|c| !"()[]{}<>".contains(*c)) {
+            Some(c) => Err(Day10Error::UnknownBraceError(c)),
+            None => Ok(line.to_string()),
         }
-    }
+    })?)
+}
 
-    println!("Total score of corrupted lines: {}", score);
+/// Total syntax error score of the corrupted lines.
+pub fn part1(lines: &[String]) -> i64 {
+    lines.iter().map(|l| score_line(l).corrupted).sum()
+}
+
+/// Middle score of completing the incomplete lines.
+pub fn part2(lines: &[String]) -> i64 {
+    let mut autocomplete_scores: Vec<i64> = lines.iter().flat_map(|l| score_line(l).autocomplete).collect();
 
     autocomplete_scores.sort();
-    println!("Middle score {}", autocomplete_scores[autocomplete_scores.len()/2]);
+    autocomplete_scores[autocomplete_scores.len()/2]
+}
 
-    Ok(())
+#[derive(Debug, Default, PartialEq)]
+pub struct LineScore {
+    /// Score of every unexpected closing brace.
+    pub corrupted: i64,
+    /// Score of the braces closing an incomplete line, if it is not corrupted.
+    pub autocomplete: Option<i64>,
+}
+
+pub fn score_line(line: &str) -> LineScore {
+    let mut score = LineScore::default();
+    let mut incomplete = false;
+    let mut balancer = Balancer::default();
+
+    for char in line.chars() {
+        if balancer.process(char).is_err() {
+            incomplete = true;
+            match Brace::from(char) {
+                Brace::Round => score.corrupted += 3,
+                Brace::Square => score.corrupted += 57,
+                Brace::Curly => score.corrupted += 1197,
+                Brace::Angled => score.corrupted += 25137,
+            }
+        }
+    }
+
+    if !incomplete && !balancer.braces.is_empty() {
+        score.autocomplete = Some(balancer.braces.iter().rev().map(|b| match b {
+            Brace::Round => 1,
+            Brace::Square => 2,
+            Brace::Curly => 3,
+            Brace::Angled => 4,
+        }).fold(0, |acc, s| acc * 5 + s));
+    }
+
+    score
 }
 
+/// Stack of the braces opened so far.
 #[derive(Default)]
-struct Balancer {
-    braces: VecDeque<Brace>
+pub struct Balancer {
+    pub braces: VecDeque<Brace>
 }
 
-#[derive(PartialEq)]
-enum Brace {
+#[derive(PartialEq, Debug)]
+pub enum Brace {
     Round,
     Square,
     Curly,
@@ -88,7 +125,7 @@ impl From<char> for Brace {
 
 
 impl Balancer {
-    fn process(&mut self, brace: char) -> Result<(), Day10Error> {
+    pub fn process(&mut self, brace: char) -> Result<(), Day10Error> {
         let kind = Brace::from(brace);
 
         match brace {
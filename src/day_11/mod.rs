@@ -1,7 +1,15 @@
+//! Day 11: Dumbo Octopus
+
 use clap::{Parser, ArgEnum};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::animate::{self, Animation};
+use crate::{cancel, config, mem};
+use crate::input::Input;
 use crate::matrix::{Matrix, MatrixError};
+use crate::observer::{NoObserver, Observer};
+use crate::{answer, debug, trace};
 
 #[derive(ArgEnum, Clone)]
 enum MatrixSize {
@@ -13,7 +21,32 @@ enum MatrixSize {
 pub struct SubCmd {
     #[clap(arg_enum)]
     matrix_size: MatrixSize,
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
+
+    /// Redraw the grid after every step, flashing octopuses highlighted
+    #[clap(long)]
+    animate: bool,
+
+    /// Animation speed in frames per second
+    #[clap(long, default_value = "10")]
+    fps: u32,
+}
+
+/// Parameters of the `[day11]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Steps whose flashes part 1 counts
+    pub steps: usize,
+    /// Steps part 2 waits for the octopuses to flash all at once
+    pub max_steps: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { steps: 100, max_steps: 1000 }
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -23,43 +56,125 @@ pub enum DayError {
     MatrixError(#[from] MatrixError),
 }
 
+/// Grid after a simulated step along with the flashes so far.
+#[derive(Debug, Clone)]
+pub struct OctopusStep<const N: usize, const M: usize> {
+    pub step: usize,
+    pub flashes: usize,
+    pub total_flashes: usize,
+    pub grid: Matrix<u8, N, M>,
+}
+
 pub fn main(args: SubCmd) -> Result<(), DayError> {
+    let input = config::input(11, args.input_filename).map_err(MatrixError::from)?;
+    let animation = args.animate.then(|| Animation::new(args.fps, input != Input::Stdin));
+    let config = &config::get().day11;
+
     match args.matrix_size {
         MatrixSize::S5x5 => {
-            let mut matrix: Matrix<u8, 5, 5> = Matrix::from_file(&args.input_filename)?;
-            simulate(&mut matrix);
+            let mut matrix: Matrix<u8, 5, 5> = mem::phase("parse", || parse(&input))?;
+            mem::phase("solve", || print_simulation(&mut matrix, config, animation));
         }
         MatrixSize::S10x10 => {
-            let mut matrix: Matrix<u8, 10, 10> = Matrix::from_file(&args.input_filename)?;
-            simulate(&mut matrix);
+            let mut matrix: Matrix<u8, 10, 10> = mem::phase("parse", || parse(&input))?;
+            mem::phase("solve", || print_simulation(&mut matrix, config, animation));
         }
     }
 
     Ok(())
 }
 
-fn simulate<const N: usize, const M: usize>(m: &mut Matrix<u8, N, M>) {
-    println!("Init:\n{}\n", m);
+/// Grid of `N` by `M` octopus energy levels.
+pub fn parse<const N: usize, const M: usize>(input: &Input) -> Result<Matrix<u8, N, M>, DayError> {
+    Ok(Matrix::from_input(input)?)
+}
+
+/// Total number of flashes after 100 steps, or as many as configured.
+pub fn part1<const N: usize, const M: usize>(m: &Matrix<u8, N, M>, config: &Config) -> usize {
+    let mut m = m.clone();
 
-    let mut total_flashes = 0;
+    (0..config.steps).map(|_| step(&mut m)).sum()
+}
+
+/// First step during which all octopuses flash, if it happens within 1000 steps or as many as configured.
+pub fn part2<const N: usize, const M: usize>(m: &Matrix<u8, N, M>, config: &Config) -> Option<usize> {
+    simulate(&mut m.clone(), config.max_steps, &mut NoObserver)
+}
+
+/// Advances the grid by one step and returns the number of octopuses which flashed.
+pub fn step<const N: usize, const M: usize>(m: &mut Matrix<u8, N, M>) -> usize {
+    cancel::checkpoint();
+    m.map(|x| x + 1);
+    explode_octopussies(m);
 
-    for step in 1..=1000 {
-        m.map(|x| x + 1);
-        explode_octopussies(m);
+    m.find_indices(|x| *x == 0).len()
+}
+
+/**
+Runs up to `max_steps` steps and returns the first one during which all octopuses flash,
+the simulation stops there. The observer sees the grid after every step.
+ **/
+pub fn simulate<const N: usize, const M: usize, O>(m: &mut Matrix<u8, N, M>, max_steps: usize, observer: &mut O) -> Option<usize>
+    where O: Observer<OctopusStep<N, M>> {
+    let mut total_flashes = 0;
 
-        let flashes = m.find_indices(|x| *x == 0).len();
+    for step in 1..=max_steps {
+        let flashes = self::step(m);
         total_flashes += flashes;
 
-        println!("Step {}, {}/{} flashes:\n{}\n", step, flashes, total_flashes, m);
+        observer.observe(&OctopusStep { step, flashes, total_flashes, grid: m.clone() });
 
         if flashes == N*M {
-            println!("First full sync on step {}", step);
-            break;
+            return Some(step);
         }
     }
+
+    None
 }
 
-fn explode_octopussies<const N: usize, const M: usize>(m: &mut Matrix<u8, N, M>) {
+/// Grid with the octopuses which just flashed highlighted.
+pub fn render<const N: usize, const M: usize>(m: &Matrix<u8, N, M>) -> String {
+    let mut res = String::new();
+
+    for pos in m.index_iter() {
+        if pos.i() != 0 && pos.j() == 0 {
+            res.push('\n');
+        }
+
+        match m.get(&pos) {
+            0 => res.push_str(&animate::highlight("0")),
+            x => res.push_str(&x.to_string()),
+        }
+    }
+
+    res
+}
+
+fn print_simulation<const N: usize, const M: usize>(m: &mut Matrix<u8, N, M>, config: &Config, mut animation: Option<Animation>) {
+    trace!("Init:\n{}\n", m);
+    if let Some(a) = animation.as_mut() {
+        a.frame(&format!("{}\nStep 0", render(m)));
+    }
+
+    // Counted apart, the simulation below stops at the sync which may come before `steps`
+    answer!("Flashes after {} steps: {}", config.steps, part1(m, config));
+
+    let sync = simulate(m, config.max_steps, &mut |e: &OctopusStep<N, M>| {
+        debug!("Step {}, {}/{} flashes", e.step, e.flashes, e.total_flashes);
+        trace!("{}\n", e.grid);
+
+        if let Some(a) = animation.as_mut() {
+            a.frame(&format!("{}\nStep {}, {} flashes, {} in total", render(&e.grid), e.step, e.flashes, e.total_flashes));
+        }
+    });
+
+    if let Some(step) = sync {
+        answer!("First full sync on step {}", step);
+    }
+}
+
+/// Resets every octopus above 9 to 0, spreading the energy to its neighbours until no more flash.
+pub fn explode_octopussies<const N: usize, const M: usize>(m: &mut Matrix<u8, N, M>) {
     let mut visit = m.find_indices(|&x| x > 9);
 
     while let Some(cur) = visit.pop() {
@@ -77,4 +192,24 @@ fn explode_octopussies<const N: usize, const M: usize>(m: &mut Matrix<u8, N, M>)
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn test_steps_snapshot() {
+        let mut m: Matrix<u8, 10, 10> = parse(&Input::path("data/11_octopusses_test.txt")).unwrap();
+        let mut grids = vec![];
+
+        simulate(&mut m, 100, &mut |e: &OctopusStep<10, 10>| {
+            if [1, 2, 10, 100].contains(&e.step) {
+                grids.push(format!("After step {}, {} flashes:\n{}\n", e.step, e.total_flashes, e.grid));
+            }
+        });
+
+        assert_snapshot("day_11_steps", &grids.join("\n"));
+    }
+}
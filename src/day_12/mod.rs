@@ -1,28 +1,37 @@
-use std::fs::File;
-use std::io;
-use std::io::BufRead;
+//! Day 12: Passage Pathing
+
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use clap::Parser;
 use thiserror::Error;
 
+use crate::{cancel, config, mem, profile};
+use crate::input::{self, Input, InputError};
+use crate::{answer, debug, trace};
+use crate::log::{self, Level};
+
 #[derive(Parser)]
 pub struct SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum DayError {
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
+    InputError(#[from] InputError),
 
-    #[error("Unparsable vertice")]
+    #[error("Expected an edge formatted as `<cave>-<cave>`")]
     WrongVerticeError,
 }
 
+/// Cave connections in both directions.
+pub type Graph = HashMap<Vertice, HashSet<Vertice>>;
+
+/// Cave, small caves are `Single` and can be visited at most once, big ones are `Multi`.
 #[derive(Eq, PartialEq, Debug, Hash, Clone)]
-enum Vertice {
+pub enum Vertice {
     Start,
     End,
     Single(String),
@@ -55,13 +64,45 @@ impl Display for Vertice {
 }
 
 pub fn main(args: SubCmd) -> Result<(), DayError> {
-    let vs = read_vertices(&args.input_filename)?;
-    println!("{:#?}", vs);
+    let vs = mem::phase("parse", || parse(&config::input(12, args.input_filename)?))?;
+    debug!("{:#?}", vs);
+
+    let res = mem::phase("solve", || paths(&vs, true));
+
+    if log::enabled(Level::Trace) {
+        for r in res.iter() {
+            trace!("{}", r.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(","));
+        }
+    }
+
+    answer!("Total paths: {}", res.len());
+
+    Ok(())
+}
+
+/// Cave system, one `<cave>-<cave>` connection per line.
+pub fn parse(input: &Input) -> Result<Graph, DayError> {
+    read_vertices(input)
+}
+
+/// Number of paths from start to end visiting small caves at most once.
+pub fn part1(graph: &Graph) -> usize {
+    paths(graph, false).len()
+}
+
+/// Number of paths from start to end when a single small cave can be visited twice.
+pub fn part2(graph: &Graph) -> usize {
+    paths(graph, true).len()
+}
 
+/// All paths from start to end, optionally allowing a single small cave to be visited twice.
+pub fn paths(vs: &Graph, visit_twice: bool) -> Vec<Vec<Vertice>> {
+    let _s = profile::span("paths");
     let mut res = vec![];
 
-    let mut paths = vec![(vec![Vertice::Start], false)];
+    let mut paths = vec![(vec![Vertice::Start], !visit_twice)];
     while let Some((longest, double)) = paths.pop() {
+        cancel::checkpoint();
         let end = longest.last().unwrap();
         for cont in vs[end].iter() {
             match cont {
@@ -74,10 +115,10 @@ pub fn main(args: SubCmd) -> Result<(), DayError> {
                 Vertice::Single(_) => {
                     let cont_self = longest.contains(cont);
 
-                    if !cont_self || (cont_self && !double) {
+                    if !cont_self || !double {
                         let mut e = longest.clone();
                         e.push(cont.clone());
-                        paths.push((e, double || (cont_self && !double)));
+                        paths.push((e, double || cont_self));
                     }
                 }
                 Vertice::Multi(_) => {
@@ -91,16 +132,7 @@ pub fn main(args: SubCmd) -> Result<(), DayError> {
         }
     }
 
-    for r in res.iter() {
-        for x in r.iter() {
-            print!("{},", x);
-        }
-        println!();
-    }
-
-    println!("Total paths: {}", res.len());
-
-    Ok(())
+    res
 }
 
 // fn contains_a_tuple<T: PartialEq>(xs: &[T], t: (&T, &T)) -> bool {
@@ -115,20 +147,52 @@ pub fn main(args: SubCmd) -> Result<(), DayError> {
 //     false
 // }
 
-fn read_vertices(filename: &str) -> Result<HashMap<Vertice, HashSet<Vertice>>, DayError> {
+fn read_vertices(input: &Input) -> Result<Graph, DayError> {
     let mut hm = HashMap::new();
 
-    let lines = io::BufReader::new(File::open(filename)?).lines();
+    let edges = input::parse_lines_by(input, |edge| {
+        edge.split_once('-')
+            .map(|(to, from)| (Vertice::from(to), Vertice::from(from)))
+            .ok_or(DayError::WrongVerticeError)
+    })?;
 
-    for edge in lines {
-        if let Some((to, from)) = edge?.split_once('-') {
-            let to: Vertice = to.into();
-            let from: Vertice = from.into();
-
-            hm.entry(to.clone()).or_insert(HashSet::new()).insert(from.clone());
-            hm.entry(from).or_insert(HashSet::new()).insert(to);
-        }
+    for (to, from) in edges {
+        hm.entry(to.clone()).or_insert(HashSet::new()).insert(from.clone());
+        hm.entry(from).or_insert(HashSet::new()).insert(to);
     }
 
     Ok(hm)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn test_visits_one_small_cave_twice() {
+        let graph = parse(&Input::path("data/12_caves_small.txt")).unwrap();
+
+        assert_eq!(part1(&graph), 10);
+        assert_eq!(part2(&graph), 36);
+        // Only the first small cave seen twice counts, start and end never do
+        assert!(paths(&graph, true).iter().all(|p| {
+            let twice = p.iter().filter(|v| matches!(v, Vertice::Single(_)) && p.iter().filter(|w| w == v).count() > 1).count();
+            twice == 0 || twice == 2
+        }));
+    }
+
+    #[test]
+    fn test_paths_snapshot() {
+        let graph = parse(&Input::path("data/12_caves_small.txt")).unwrap();
+
+        for (name, visit_twice) in [("day_12_paths", false), ("day_12_paths_twice", true)] {
+            let mut paths: Vec<String> = paths(&graph, visit_twice).iter()
+                .map(|p| p.iter().map(Vertice::to_string).collect::<Vec<_>>().join(","))
+                .collect();
+            paths.sort();
+
+            assert_snapshot(name, &paths.join("\n"));
+        }
+    }
+}
@@ -1,17 +1,34 @@
+//! Day 13: Transparent Origami
+
 use std::collections::HashSet;
 use clap::Parser;
 use thiserror::Error;
 
+use crate::animate::{self, Animation};
+use crate::{config, mem};
+use crate::input::{Input, InputError};
+use crate::observer::{NoObserver, Observer};
+use crate::{answer, debug, info};
+
 #[derive(Parser)]
 pub struct SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
+
+    /// Redraw the paper after every fold, the next fold line highlighted
+    #[clap(long)]
+    animate: bool,
+
+    /// Animation speed in frames per second
+    #[clap(long, default_value = "1")]
+    fps: u32,
 }
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum DayError {
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
+    InputError(#[from] InputError),
 
     #[error("Unable to parse the number")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -19,14 +36,14 @@ pub enum DayError {
     #[error("Unknown dimension specifier")]
     UnknownDimensionError,
 
-    #[error(transparent)]
-    ParsingError(#[from] nom::error::Error<String>),
+    #[error("Dots lie further past the fold {0:?} than the fold is from the edge")]
+    FoldPastEdge(Fold),
 }
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
 pub struct Point {
-    x: usize,
-    y: usize,
+    pub x: usize,
+    pub y: usize,
 }
 
 impl TryFrom<(&str, &str)> for Point {
@@ -37,7 +54,7 @@ impl TryFrom<(&str, &str)> for Point {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Fold {
     X(usize),
     Y(usize),
@@ -56,57 +73,146 @@ impl TryFrom<(char, &str)> for Fold {
 }
 
 pub fn main(args: SubCmd) -> Result<(), DayError> {
-    let (points, folds) = reader::read_file(&args.input_filename)?;
-    let mut points = HashSet::from_iter(points);
+    let input = config::input(13, args.input_filename)?;
+    let (points, folds) = mem::phase("parse", || parse(&input))?;
+
+    let mut animation = args.animate.then(|| Animation::new(args.fps, input != Input::Stdin));
+    if let Some(a) = animation.as_mut() {
+        a.frame(&format!("{}{} dots", render(&HashSet::from_iter(points.iter().copied()), folds.first()), points.len()));
+    }
+
+    let mut first_fold = None;
 
-    for f in folds.iter() {
-        points = fold(&points, f);
-        if points.len() < 200 {
-            viz(&points);
+    let last = mem::phase("solve", || fold_all(&points, &folds, &mut |e: &FoldStep| {
+        if e.points.len() < 200 {
+            debug!("{}", viz(&e.points));
         }
-        println!("Points after fold {:?}: {}", f, points.len());
-        println!();
+        info!("Points after fold {:?}: {}", e.fold, e.points.len());
+
+        if let Some(a) = animation.as_mut() {
+            a.frame(&format!("{}{} dots after fold {:?}", render(&e.points, folds.get(e.index + 1)), e.points.len(), e.fold));
+        }
+
+        if e.index == 0 {
+            first_fold = Some((e.fold, e.points.len()));
+        }
+    }));
+    let points = last.map_err(DayError::FoldPastEdge)?;
+
+    if let Some((fold, dots)) = first_fold {
+        answer!("Points after first fold {:?}: {}", fold, dots);
     }
+    answer!("{}", viz(&points).trim_end());
 
     Ok(())
 }
 
-fn viz(points: &HashSet<Point>) {
-    let max_x = points.iter().max_by_key(|p| p.x).unwrap().x;
-    let max_y = points.iter().max_by_key(|p| p.y).unwrap().y;
+/// Dots, one `x,y` per line, followed by `fold along x=N` instructions after a blank line.
+pub fn parse(input: &Input) -> Result<(Vec<Point>, Vec<Fold>), DayError> {
+    Ok(reader::read_file(input)?)
+}
+
+/// Number of dots visible after the first fold.
+pub fn part1(points: &[Point], folds: &[Fold]) -> Option<usize> {
+    let points = HashSet::from_iter(points.iter().copied());
 
+    match folds.first() {
+        Some(f) => fold(&points, f).map(|p| p.len()),
+        None => Some(points.len()),
+    }
+}
+
+/// Code revealed after all folds, drawn with `#` for dots and `.` for blanks.
+pub fn part2(points: &[Point], folds: &[Fold]) -> Option<String> {
+    fold_all(points, folds, &mut NoObserver).ok().map(|p| viz(&p))
+}
+
+/// Dots left after the fold with the given `index`.
+#[derive(Debug, Clone)]
+pub struct FoldStep {
+    pub index: usize,
+    pub fold: Fold,
+    pub points: HashSet<Point>,
+}
+
+/// Applies the folds in order, the observer sees the dots after each one. Fails with the first
+/// fold which would put dots past the edge, see [`fold`].
+pub fn fold_all<O: Observer<FoldStep>>(points: &[Point], folds: &[Fold], observer: &mut O) -> Result<HashSet<Point>, Fold> {
+    let mut points = HashSet::from_iter(points.iter().copied());
+
+    for (index, f) in folds.iter().enumerate() {
+        points = fold(&points, f).ok_or(*f)?;
+        observer.observe(&FoldStep { index, fold: *f, points: points.clone() });
+    }
+
+    Ok(points)
+}
+
+/// Draws the dots line by line.
+pub fn viz(points: &HashSet<Point>) -> String {
+    let (max_x, max_y) = match (points.iter().map(|p| p.x).max(), points.iter().map(|p| p.y).max()) {
+        (Some(max_x), Some(max_y)) => (max_x, max_y),
+        _ => return String::new(),
+    };
+
+    let mut res = String::new();
     for y in 0..=max_y {
         for x in 0..=max_x {
             if points.contains(&Point { x, y }) {
-                print!("#");
+                res.push('#');
             } else {
-                print!(".");
+                res.push('.');
             }
         }
-        println!();
+        res.push('\n');
     }
+
+    res
 }
 
-fn fold(points: &HashSet<Point>, fold: &Fold) -> HashSet<Point> {
-    let (top, bottom) = split_on_fold(points, fold);
-    let mirrored = mirror_on_fold(&bottom, fold);
+/// Same as [`viz`], with the line of the upcoming fold highlighted.
+pub fn render(points: &HashSet<Point>, next: Option<&Fold>) -> String {
+    let max_x = points.iter().map(|p| p.x).max().unwrap_or(0);
+    let max_y = points.iter().map(|p| p.y).max().unwrap_or(0);
 
-    top.union(&mirrored).map(|p| p.to_owned()).collect()
+    let mut res = String::new();
+    for y in 0..=max_y {
+        for x in 0..=max_x {
+            match next {
+                Some(Fold::X(fx)) if x == *fx => res.push_str(&animate::highlight("|")),
+                Some(Fold::Y(fy)) if y == *fy => res.push_str(&animate::highlight("-")),
+                _ if points.contains(&Point { x, y }) => res.push('#'),
+                _ => res.push('.'),
+            }
+        }
+        res.push('\n');
+    }
+
+    res
 }
 
-fn mirror_on_fold(points: &HashSet<Point>, fold: &Fold) -> HashSet<Point> {
-    let max_x = points.iter().max_by_key(|p| p.x).unwrap().x;
-    let max_y = points.iter().max_by_key(|p| p.y).unwrap().y;
+/**
+Folds the bottom (or right) half of the paper up (or left) over the top half. `None` when the
+bottom half is larger, its furthest dots would land past the edge of the paper.
+ **/
+pub fn fold(points: &HashSet<Point>, fold: &Fold) -> Option<HashSet<Point>> {
+    let (top, bottom) = split_on_fold(points, fold);
+    let mirrored = mirror_on_fold(&bottom, fold)?;
 
+    Some(top.union(&mirrored).map(|p| p.to_owned()).collect())
+}
+
+/// Mirrors the dots across the fold line, `None` if any ends up at a negative position.
+pub fn mirror_on_fold(points: &HashSet<Point>, fold: &Fold) -> Option<HashSet<Point>> {
     points.iter().map(|p| {
         match fold {
-            Fold::X(x) => Point { x: x - (p.x - (max_x - x)), y: p.y },
-            Fold::Y(y) => Point { x: p.x, y: y - (p.y - (max_y - y)) }
+            Fold::X(x) => Some(Point { x: (2 * x).checked_sub(p.x)?, y: p.y }),
+            Fold::Y(y) => Some(Point { x: p.x, y: (2 * y).checked_sub(p.y)? }),
         }
     }).collect()
 }
 
-fn split_on_fold(points: &HashSet<Point>, fold: &Fold) -> (HashSet<Point>, HashSet<Point>) {
+pub fn split_on_fold(points: &HashSet<Point>, fold: &Fold) -> (HashSet<Point>, HashSet<Point>) {
     points.iter().partition(|p| {
         match fold {
             Fold::X(x) => p.x < *x,
@@ -116,30 +222,31 @@ fn split_on_fold(points: &HashSet<Point>, fold: &Fold) -> (HashSet<Point>, HashS
 }
 
 mod reader {
-    use nom::{Finish, IResult};
+    use nom::IResult;
     use nom::bytes::complete::tag;
-    use nom::character::complete::{digit1, multispace1, one_of};
+    use nom::character::complete::{digit1, one_of};
     use nom::combinator::map_res;
-    use nom::multi::separated_list1;
     use nom::sequence::{preceded, separated_pair};
 
-    use crate::day_13::DayError;
+    use crate::input::{self, Input, InputError, ParseError};
 
     use super::{Fold, Point};
 
-    pub fn read_file(fname: &str) -> Result<(Vec<Point>, Vec<Fold>), DayError> {
-        Ok(parse_input(&std::fs::read_to_string(fname)?)
-            .map(|(_, r)| r)
-            .map_err(|e| e.to_owned())
-            .finish()?)
-    }
-
-    fn parse_input(input: &str) -> IResult<&str, (Vec<Point>, Vec<Fold>)> {
-        separated_pair(points, multispace1, folds)(input)
-    }
+    pub fn read_file(input: &Input) -> Result<(Vec<Point>, Vec<Fold>), InputError> {
+        let blocks = input::blocks(input)?;
 
-    fn points(input: &str) -> IResult<&str, Vec<Point>> {
-        separated_list1(multispace1, point)(input)
+        match blocks.as_slice() {
+            [points, folds] => input::join(
+                input::parse_block_with(input, points, point),
+                input::parse_block_with(input, folds, fold),
+            ),
+            [points] => {
+                let last = points.lines.len() - 1;
+                Err(ParseError::new(input, points.line + last, &points.lines[last], "Expected fold instructions after a blank line").into())
+            }
+            [_, _, extra, ..] => Err(ParseError::new(input, extra.line, &extra.lines[0], "Unexpected lines after the fold instructions").into()),
+            [] => Err(ParseError::new(input, 1, "", "Expected dots, got an empty input").into()),
+        }
     }
 
     fn point(input: &str) -> IResult<&str, Point> {
@@ -149,10 +256,6 @@ mod reader {
         )(input)
     }
 
-    fn folds(input: &str) -> IResult<&str, Vec<Fold>> {
-        separated_list1(multispace1, fold)(input)
-    }
-
     fn fold(input: &str) -> IResult<&str, Fold> {
         preceded(
             tag("fold along "),
@@ -170,4 +273,40 @@ mod reader {
             Fold::try_from,
         )(input)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn test_folds_snapshot() {
+        let (points, folds) = parse(&Input::path("data/13_folds_test.txt")).unwrap();
+        let mut drawings = vec![format!("Dots:\n{}", viz(&HashSet::from_iter(points.iter().copied())))];
+
+        fold_all(&points, &folds, &mut |e: &FoldStep| {
+            drawings.push(format!("After fold {}, {:?}:\n{}", e.index + 1, e.fold, viz(&e.points)));
+        }).unwrap();
+
+        assert_snapshot("day_13_folds", &drawings.join("\n"));
+    }
+
+    #[test]
+    fn test_mirrors_across_the_fold_line() {
+        // Mirroring by the furthest dot instead, as `max_x - p.x`, would put (3, 0) on (0, 0)
+        let points = HashSet::from([Point { x: 0, y: 0 }, Point { x: 3, y: 0 }]);
+
+        assert_eq!(fold(&points, &Fold::X(2)), Some(HashSet::from([Point { x: 0, y: 0 }, Point { x: 1, y: 0 }])));
+    }
+
+    #[test]
+    fn test_folds_near_the_edges() {
+        let points = HashSet::from([Point { x: 0, y: 0 }, Point { x: 3, y: 1 }]);
+
+        assert_eq!(fold(&points, &Fold::X(1)), None);
+        assert_eq!(fold(&points, &Fold::X(2)), Some(HashSet::from([Point { x: 0, y: 0 }, Point { x: 1, y: 1 }])));
+        assert_eq!(fold(&points, &Fold::Y(5)), Some(points.clone()));
+        assert_eq!(viz(&HashSet::new()), "");
+    }
+}
@@ -1,28 +1,130 @@
+//! Day 14: Extended Polymerization
+
 use std::collections::HashMap;
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-type Insertion = ((char, char), char);
+use crate::{cancel, config, mem, profile};
+use crate::input::{Input, InputError};
+use crate::observer::{NoObserver, Observer};
+use crate::{answer, debug};
+
+/// Pair insertion rule, `AB -> C`.
+pub type Insertion = ((char, char), char);
 
 #[derive(Parser)]
 pub struct SubCmd {
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
+}
+
+/// Parameters of the `[day14]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Insertion steps before part 1 takes the spread
+    pub part1_steps: usize,
+    /// Insertion steps before part 2 takes the spread
+    pub part2_steps: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { part1_steps: 10, part2_steps: 40 }
+    }
+}
+
+/// Steps after which the counts could overflow, they at most double every step.
+pub const MAX_STEPS: usize = 50;
+
+impl Config {
+    pub fn check(&self) -> Result<(), String> {
+        match [("part1_steps", self.part1_steps), ("part2_steps", self.part2_steps)].into_iter().find(|&(_, s)| s > MAX_STEPS) {
+            Some((name, steps)) => Err(format!("Expected day14.{} to be at most {}, got {}", name, MAX_STEPS, steps)),
+            None => Ok(()),
+        }
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum DayError {
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
-
-    #[error(transparent)]
-    ParsingError(#[from] nom::error::Error<String>),
+    InputError(#[from] InputError),
 }
 
 pub fn main(args: SubCmd) -> Result<(), DayError> {
-    let (tmpl, ins) = reader::read_file(&args.input_filename)?;
+    let (tmpl, ins) = mem::phase("parse", || parse(&config::input(14, args.input_filename)?))?;
+    let config = &config::get().day14;
+
+    mem::phase("solve", || simulate(&tmpl, &ins, config.part1_steps.max(config.part2_steps), &mut |e: &PolymerStep| {
+        let common = e.char_tally.iter().max_by_key(|(_, f)| *f).unwrap();
+        let rare = e.char_tally.iter().min_by_key(|(_, f)| *f).unwrap();
+
+        if e.step == config.part1_steps || e.step == config.part2_steps {
+            answer!("Step {}, most common {}, least common {}, diff = {}", e.step, fmt_pair(common), fmt_pair(rare), common.1 - rare.1);
+        } else {
+            debug!("Step {}, most common {}, least common {}, diff = {}", e.step, fmt_pair(common), fmt_pair(rare), common.1 - rare.1);
+        }
+    }));
+
+    Ok(())
+}
+
+/// Polymer template on the first line, followed by insertion rules after a blank line.
+pub fn parse(input: &Input) -> Result<(String, Vec<Insertion>), DayError> {
+    Ok(reader::read_file(input)?)
+}
+
+/// Most common minus least common element quantity after 10 steps, or as many as configured.
+pub fn part1(tmpl: &str, ins: &[Insertion], config: &Config) -> usize {
+    spread(&polymerize(tmpl, ins, config.part1_steps))
+}
+
+/// Most common minus least common element quantity after 40 steps, or as many as configured.
+pub fn part2(tmpl: &str, ins: &[Insertion], config: &Config) -> usize {
+    spread(&polymerize(tmpl, ins, config.part2_steps))
+}
+
+/// Element quantities of the polymer after applying insertion rules `steps` times.
+pub fn polymerize(tmpl: &str, ins: &[Insertion], steps: usize) -> HashMap<char, usize> {
+    simulate(tmpl, ins, steps, &mut NoObserver)
+}
+
+/// Element quantities after a polymerization step.
+#[derive(Debug, Clone)]
+pub struct PolymerStep {
+    pub step: usize,
+    pub char_tally: HashMap<char, usize>,
+}
+
+/// Same as [`polymerize`], the observer sees the element quantities after every step.
+pub fn simulate<O: Observer<PolymerStep>>(tmpl: &str, ins: &[Insertion], steps: usize, observer: &mut O) -> HashMap<char, usize> {
+    let _s = profile::span("polymerize");
+    let ins: HashMap<(char, char), char> = ins.iter().copied().collect();
+    let (mut tally, mut char_tally) = tallies(tmpl);
+
+    for s in 1..=steps {
+        cancel::checkpoint();
+        let (t, ct) = step(&tally, &char_tally, &ins);
+        tally = t;
+        char_tally = ct;
+
+        observer.observe(&PolymerStep { step: s, char_tally: char_tally.clone() });
+    }
+
+    char_tally
+}
+
+/// Difference between the most and the least common element quantity.
+pub fn spread(char_tally: &HashMap<char, usize>) -> usize {
+    char_tally.values().max().unwrap_or(&0) - char_tally.values().min().unwrap_or(&0)
+}
+
+/// Quantities of adjacent pairs and of single elements in the template.
+pub fn tallies(tmpl: &str) -> (HashMap<(char, char), usize>, HashMap<char, usize>) {
     let pairs: Vec<(char, char)> = tmpl.chars().zip(tmpl.chars().skip(1)).collect();
-    let ins: HashMap<(char, char), char> = HashMap::from_iter(ins);
 
     let mut char_tally = HashMap::new();
     for c in tmpl.chars() {
@@ -34,26 +136,15 @@ pub fn main(args: SubCmd) -> Result<(), DayError> {
         tally.entry(p).and_modify(|v| *v += 1).or_insert(1);
     }
 
-    for s in 1..=40 {
-        let (t, ct) = step(&tally, &char_tally, &ins);
-        tally = t;
-        char_tally = ct;
-        // let cnt = char_count(&tally);
-        let common = char_tally.iter().max_by_key(|(_, f)| *f).unwrap();
-        let rare = char_tally.iter().min_by_key(|(_, f)| *f).unwrap();
-
-        println!("Step {}, most common {}, least common {}, diff = {}", s, fmt_pair(common), fmt_pair(rare), common.1 - rare.1);
-    }
-
-
-    Ok(())
+    (tally, char_tally)
 }
 
 fn fmt_pair(p: (&char, &usize)) -> String {
-    format!("({}, {})", p.0.to_string(), p.1)
+    format!("({}, {})", p.0, p.1)
 }
 
-fn step(tally: &HashMap<(char, char), usize>, char_tally: &HashMap<char, usize>, ins: &HashMap<(char, char), char>) -> (HashMap<(char, char), usize>, HashMap<char, usize>) {
+/// Applies the insertion rules once, counting pairs instead of building the polymer.
+pub fn step(tally: &HashMap<(char, char), usize>, char_tally: &HashMap<char, usize>, ins: &HashMap<(char, char), char>) -> (HashMap<(char, char), usize>, HashMap<char, usize>) {
     let mut res = HashMap::new();
     let mut char_res = char_tally.clone();
 
@@ -87,20 +178,33 @@ fn step(tally: &HashMap<(char, char), usize>, char_tally: &HashMap<char, usize>,
 }
 
 mod reader {
-    use nom::{Finish, IResult};
+    use nom::IResult;
     use nom::bytes::complete::tag;
-    use nom::character::complete::{alpha1, anychar, multispace1};
+    use nom::character::complete::{alpha1, anychar};
     use nom::combinator::map;
-    use nom::multi::separated_list1;
     use nom::sequence::{pair, separated_pair};
 
-    use crate::day_14::{DayError, Insertion};
-
-    pub fn read_file(fname: &str) -> Result<(String, Vec<Insertion>), DayError> {
-        Ok(parse_input(&std::fs::read_to_string(fname)?)
-            .map(|(_, r)| r)
-            .map_err(|e| e.to_owned())
-            .finish()?)
+    use crate::input::{self, Input, InputError, ParseError};
+    use crate::day_14::Insertion;
+
+    pub fn read_file(input: &Input) -> Result<(String, Vec<Insertion>), InputError> {
+        let blocks = input::blocks(input)?;
+
+        match blocks.as_slice() {
+            [tmpl, ..] if tmpl.lines.len() > 1 =>
+                Err(ParseError::new(input, tmpl.line + 1, &tmpl.lines[1], "Expected a blank line after the polymer template").into()),
+            [tmpl, rules] => {
+                let (mut tmpl, rules) = input::join(
+                    input::parse_block_with(input, tmpl, template),
+                    input::parse_block_with(input, rules, insertion),
+                )?;
+
+                Ok((tmpl.pop().unwrap_or_default(), rules))
+            }
+            [tmpl] => Err(ParseError::new(input, tmpl.line, &tmpl.lines[0], "Expected insertion rules after a blank line").into()),
+            [_, _, extra, ..] => Err(ParseError::new(input, extra.line, &extra.lines[0], "Unexpected lines after the insertion rules").into()),
+            [] => Err(ParseError::new(input, 1, "", "Expected a polymer template, got an empty input").into()),
+        }
     }
 
     fn template(input: &str) -> IResult<&str, String> {
@@ -111,11 +215,4 @@ mod reader {
         separated_pair(pair(anychar, anychar), tag(" -> "), anychar)(input)
     }
 
-    fn insertions(input: &str) -> IResult<&str, Vec<Insertion>> {
-        separated_list1(multispace1, insertion)(input)
-    }
-
-    fn parse_input(input: &str) -> IResult<&str, (String, Vec<Insertion>)> {
-        separated_pair(template, multispace1, insertions)(input)
-    }
 }
\ No newline at end of file
@@ -1,12 +1,16 @@
+//! Day 15: Chiton
+
 use std::cmp::{min, Ordering, Reverse};
 use std::collections::BinaryHeap;
-use std::usize;
 
 use clap::{ArgEnum, Parser};
 use hashbrown::HashSet;
 use thiserror::Error;
 
+use crate::{cancel, config, mem, profile};
+use crate::input::Input;
 use crate::matrix::{Matrix, MatrixError, Pos};
+use crate::answer;
 
 #[derive(ArgEnum, Clone)]
 enum MatrixSize {
@@ -20,7 +24,8 @@ pub struct SubCmd {
     matrix_size: MatrixSize,
     #[clap(long, parse(from_flag))]
     large_cave: bool,
-    input_filename: String,
+    /// Defaults to the input set in the configuration
+    pub input_filename: Option<Input>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -31,36 +36,82 @@ pub enum DayError {
 }
 
 pub fn main(args: SubCmd) -> Result<(), DayError> {
+    let input = config::input(15, args.input_filename).map_err(MatrixError::from)?;
+
     match (args.matrix_size, args.large_cave) {
         (MatrixSize::S10x10, false) => {
-            solve::<10, 10>(&args.input_filename)?;
+            solve::<10, 10>(&input)?;
         }
         (MatrixSize::S10x10, true) => {
-            solve::<10, 50>(&args.input_filename)?;
+            solve::<10, 50>(&input)?;
         }
         (MatrixSize::S100x100, false) => {
-            solve::<100, 100>(&args.input_filename)?;
+            solve::<100, 100>(&input)?;
         }
         (MatrixSize::S100x100, true) => {
-            solve::<100, 500>(&args.input_filename)?;
+            solve::<100, 500>(&input)?;
         }
     }
 
     Ok(())
 }
 
-fn solve<const N: usize, const M: usize>(fname: &str) -> Result<(), DayError> {
-    let matrix: Matrix<u8, N, N> = Matrix::from_file(fname)?;
-    let matrix: Matrix<u8, M, M> = extend_matrix(&matrix);
-    let end_pos = Pos::new(M - 1, M - 1);
+fn solve<const N: usize, const M: usize>(input: &Input) -> Result<(), DayError> {
+    let matrix: Matrix<u8, N, N> = mem::phase("parse", || parse(input))?;
+    let risk = mem::phase("solve", || lowest_risk(&extend_matrix::<N, M>(&matrix)));
 
-    let dist = distances(&matrix, &end_pos);
-    println!("Minimal risk: {}", dist.get(&end_pos));
+    answer!("Minimal risk: {}", risk);
 
     Ok(())
 }
 
-fn extend_matrix<const N: usize, const M: usize>(m: &Matrix<u8, N, N>) -> Matrix<u8, M, M> {
+/// Square risk level map of `N` by `N` digits.
+pub fn parse<const N: usize>(input: &Input) -> Result<Matrix<u8, N, N>, DayError> {
+    Ok(Matrix::from_input(input)?)
+}
+
+/// Lowest total risk of a path from the top left to the bottom right corner.
+pub fn part1<const N: usize>(m: &Matrix<u8, N, N>) -> usize {
+    lowest_risk(m)
+}
+
+/// Lowest total risk on the map tiled five times in each direction, `M` must be `5 * N`.
+pub fn part2<const N: usize, const M: usize>(m: &Matrix<u8, N, N>) -> usize {
+    const { assert!(M == 5 * N, "The tiled map of part 2 is 5 * N wide") };
+    lowest_risk(&extend_matrix::<N, M>(m))
+}
+
+/// Lowest total risk of a path from the top left to the bottom right corner.
+pub fn lowest_risk<const N: usize>(m: &Matrix<u8, N, N>) -> usize {
+    let end_pos = Pos::new(N - 1, N - 1);
+
+    *distances(m, &end_pos).get(&end_pos)
+}
+
+/// Positions of a lowest risk path from the top left to the bottom right corner, both included.
+pub fn lowest_risk_path<const N: usize>(m: &Matrix<u8, N, N>) -> Vec<Pos<N, N>> {
+    let end_pos = Pos::new(N - 1, N - 1);
+    let distances = distances(m, &end_pos);
+
+    // Walking back over neighbours whose distance accounts for the step exactly
+    let mut path = vec![end_pos];
+    let mut cur = end_pos;
+    while cur != Pos::new(0, 0) {
+        let step = *distances.get(&cur) - *m.get(&cur) as usize;
+        cur = m.direct_neighbours(&cur)
+            .find(|p| *distances.get(p) == step)
+            .expect("Every position on the path comes from a neighbour");
+        path.push(cur);
+    }
+
+    path.reverse();
+    path
+}
+
+/// Tiles the map to `M` by `M`, every tile to the right or down adds one to the risk,
+/// wrapping above 9 back to 1.
+pub fn extend_matrix<const N: usize, const M: usize>(m: &Matrix<u8, N, N>) -> Matrix<u8, M, M> {
+    let _s = profile::span("extend_matrix");
     let mut r: Matrix<u8, M, M> = Matrix::new(0);
     for i in 0..M {
         let imul = (i / N) as u8;
@@ -83,18 +134,13 @@ struct WeightedPos<const N: usize> {
 
 impl<const N: usize> PartialOrd for WeightedPos<N> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.weight {
-            w if w > other.weight => Some(Ordering::Greater),
-            w if w == other.weight => Some(Ordering::Equal),
-            w if w < other.weight => Some(Ordering::Less),
-            _ => None
-        }
+        Some(self.cmp(other))
     }
 }
 
 impl<const N: usize> Ord for WeightedPos<N> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.weight.cmp(&other.weight)
     }
 }
 
@@ -102,8 +148,14 @@ impl<const N: usize> Ord for WeightedPos<N> {
 Implements Dijkstra algorithm imagining the matrix as a set of connected nodes with
 the front moving towards the bottom-right corner
  **/
-fn distances<const N: usize>(m: &Matrix<u8, N, N>, finish_pos: &Pos<N, N>) -> Matrix<usize, N, N> {
-    let initial_pos = Pos::new(0, 0);
+pub fn distances<const N: usize>(m: &Matrix<u8, N, N>, finish_pos: &Pos<N, N>) -> Matrix<usize, N, N> {
+    distances_from(m, &Pos::new(0, 0), finish_pos)
+}
+
+/// Same as [`distances`], starting from any position instead of the top left corner.
+pub fn distances_from<const N: usize>(m: &Matrix<u8, N, N>, initial_pos: &Pos<N, N>, finish_pos: &Pos<N, N>) -> Matrix<usize, N, N> {
+    let _s = profile::span("distances");
+    let initial_pos = *initial_pos;
 
     let mut distances = Matrix::new(usize::MAX);
     distances.set(&initial_pos, 0);
@@ -113,6 +165,7 @@ fn distances<const N: usize>(m: &Matrix<u8, N, N>, finish_pos: &Pos<N, N>) -> Ma
     front.push(Reverse(WeightedPos { pos: initial_pos, weight: 0 }));
 
     while let Some(Reverse(WeightedPos { pos: cur, weight: cur_dist })) = front.pop() {
+        cancel::checkpoint();
         if visited.contains(&cur) {
             continue;
         } else if &cur == finish_pos {
@@ -0,0 +1,75 @@
+//! Pictures of puzzle grids, drawn as SVG so they can be embedded anywhere.
+
+use std::fmt::Write;
+
+/// Background the cells without a colour are left on.
+const BACKGROUND: &str = "#0f0f23";
+
+/// Grid of coloured cells with a caption, `rows` by `cols` cells in size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Figure {
+    pub caption: String,
+    pub rows: usize,
+    pub cols: usize,
+    /// Colour of every cell, row by row, none showing the background
+    cells: Vec<Option<String>>,
+}
+
+impl Figure {
+    pub fn new(caption: &str, rows: usize, cols: usize) -> Figure {
+        Figure { caption: caption.to_string(), rows, cols, cells: vec![None; rows * cols] }
+    }
+
+    /// Colours a cell with any CSS colour.
+    pub fn paint(&mut self, row: usize, col: usize, colour: &str) {
+        self.cells[row * self.cols + col] = Some(colour.to_string());
+    }
+
+    pub fn colour(&self, row: usize, col: usize) -> Option<&str> {
+        self.cells[row * self.cols + col].as_deref()
+    }
+
+    /// The grid as an SVG element `scale` pixels per cell, runs of a colour drawn as one rectangle.
+    pub fn svg(&self, scale: usize) -> String {
+        let mut svg = String::new();
+        let (width, height) = (self.cols * scale, self.rows * scale);
+
+        write!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+               width, height, self.cols, self.rows).expect("Writing to a String can't fail");
+        write!(svg, r#"<rect width="{}" height="{}" fill="{}"/>"#, self.cols, self.rows, BACKGROUND).expect("Writing to a String can't fail");
+
+        for row in 0..self.rows {
+            let mut col = 0;
+            while col < self.cols {
+                let colour = self.colour(row, col);
+                let run = (col..self.cols).take_while(|&c| self.colour(row, c) == colour).count();
+
+                if let Some(colour) = colour {
+                    write!(svg, r#"<rect x="{}" y="{}" width="{}" height="1" fill="{}"/>"#, col, row, run, colour)
+                        .expect("Writing to a String can't fail");
+                }
+                col += run;
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merges_runs() {
+        let mut figure = Figure::new("dots", 2, 4);
+        figure.paint(0, 1, "red");
+        figure.paint(0, 2, "red");
+        figure.paint(1, 3, "blue");
+
+        let svg = figure.svg(10);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20" viewBox="0 0 4 2""#));
+        assert!(svg.contains(r#"<rect x="1" y="0" width="2" height="1" fill="red"/><rect x="3" y="1" width="1" height="1" fill="blue"/></svg>"#));
+    }
+}
@@ -0,0 +1,488 @@
+//! Random puzzle inputs, reproducible from a seed.
+//!
+//! Every generator writes an input the day's parser accepts and its solver can answer: bingo
+//! draws contain every board number, display entries are encoded with an actual wiring, caves
+//! never connect two big caves (the paths would be endless) and folds always halve the paper.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::Parser;
+use thiserror::Error;
+
+use crate::AdventError;
+use crate::rng::Rng;
+
+#[derive(Parser)]
+pub struct SubCmd {
+    day: u8,
+
+    /// Seed of the generator, the same seed always gives the same input
+    #[clap(long, default_value = "1")]
+    seed: u64,
+
+    /// Number of records: measurements, commands, boards, lines, fishes, crabs, display
+    /// entries, navigation lines, caves, dots or the length of the polymer template
+    #[clap(long)]
+    size: Option<usize>,
+
+    /// Grid of days 9, 11 and 15 as ROWSxCOLS, for day 3 the readings and their bit count
+    #[clap(long)]
+    grid: Option<Dims>,
+
+    /// Share of diagonal lines in day 5 inputs, from 0 to 1
+    #[clap(long, default_value = "0.25")]
+    diagonals: f64,
+
+    /// Elements of the day 14 polymer
+    #[clap(long, default_value = "BCHN")]
+    alphabet: String,
+
+    /// File to write the input to instead of stdout
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+pub enum GenError {
+    #[error("There is no generator for day {0}")]
+    UnknownDay(u8),
+
+    #[error("{0}")]
+    InvalidParameter(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dims {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl FromStr for Dims {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| n.parse::<usize>().ok().filter(|&n| n > 0);
+
+        match s.split_once('x').map(|(r, c)| (parse(r), parse(c))) {
+            Some((Some(rows), Some(cols))) => Ok(Dims { rows, cols }),
+            _ => Err(format!("Expected ROWSxCOLS, got {:?}", s)),
+        }
+    }
+}
+
+/// Knobs of the generators, each day uses the ones that make sense for it.
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub size: Option<usize>,
+    pub grid: Option<Dims>,
+    pub diagonals: f64,
+    pub alphabet: String,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { size: None, grid: None, diagonals: 0.25, alphabet: "BCHN".to_string() }
+    }
+}
+
+impl Params {
+    fn size(&self, default: usize) -> usize {
+        self.size.unwrap_or(default)
+    }
+
+    fn grid(&self, rows: usize, cols: usize) -> Dims {
+        self.grid.unwrap_or(Dims { rows, cols })
+    }
+}
+
+fn invalid<T>(message: String) -> Result<T, GenError> {
+    Err(GenError::InvalidParameter(message))
+}
+
+pub fn main(args: SubCmd) -> Result<(), AdventError> {
+    let params = Params { size: args.size, grid: args.grid, diagonals: args.diagonals, alphabet: args.alphabet };
+    let input = generate(args.day, &params, &mut Rng::new(args.seed))?;
+
+    match args.output {
+        Some(path) => fs::write(path, input).map_err(|e| AdventError::InputError(e.into()))?,
+        None => print!("{}", input),
+    }
+
+    Ok(())
+}
+
+/// Input of the day drawn from `rng`.
+pub fn generate(day: u8, params: &Params, rng: &mut Rng) -> Result<String, GenError> {
+    if params.size == Some(0) {
+        return invalid("Expected a size of at least 1".to_string());
+    }
+
+    match day {
+        1 => Ok(sonar_sweeps(params, rng)),
+        2 => Ok(commands(params, rng)),
+        3 => sensor_bits(params, rng),
+        4 => bingo(params, rng),
+        5 => vent_lines(params, rng),
+        6 => Ok(numbers(params.size(300), 1..=5, rng)),
+        7 => {
+            let crabs = params.size(1000);
+            Ok(numbers(crabs, 0..=crabs * 2, rng))
+        }
+        8 => Ok(displays(params, rng)),
+        9 => Ok(digit_grid(params.grid(5, 10), rng, |rng| match rng.chance(0.2) {
+            true => 9,
+            false => rng.below(9),
+        })),
+        10 => Ok(navigation(params, rng)),
+        11 => Ok(digit_grid(params.grid(10, 10), rng, |rng| rng.below(10))),
+        12 => caves(params, rng),
+        13 => Ok(origami(params, rng)),
+        14 => polymer(params, rng),
+        15 => Ok(digit_grid(params.grid(10, 10), rng, |rng| rng.between(1..=9))),
+        _ => Err(GenError::UnknownDay(day)),
+    }
+}
+
+fn lines<I: IntoIterator<Item=String>>(lines: I) -> String {
+    lines.into_iter().map(|l| l + "\n").collect()
+}
+
+/// Depths drifting mostly downwards.
+fn sonar_sweeps(params: &Params, rng: &mut Rng) -> String {
+    let mut depth = rng.between(100..=200);
+
+    lines((0..params.size(200)).map(|_| {
+        depth = (depth + rng.below(31)).saturating_sub(10);
+        depth.to_string()
+    }))
+}
+
+/// Steering commands never taking the submarine above the surface.
+fn commands(params: &Params, rng: &mut Rng) -> String {
+    let mut depth = 0;
+
+    lines((0..params.size(100)).map(|_| {
+        let amount = rng.between(1..=9);
+        match rng.below(3) {
+            0 if depth >= amount => {
+                depth -= amount;
+                format!("up {}", amount)
+            }
+            1 => {
+                depth += amount;
+                format!("down {}", amount)
+            }
+            _ => format!("forward {}", amount),
+        }
+    }))
+}
+
+/// Distinct readings, duplicates would leave the rating filters with several candidates.
+fn sensor_bits(params: &Params, rng: &mut Rng) -> Result<String, GenError> {
+    let Dims { rows, cols } = params.grid(params.size(100), 12);
+    if cols > 32 {
+        return invalid(format!("Readings of {} bits don't fit the 32 bit ratings", cols));
+    }
+    if rows as u64 > 1 << cols {
+        return invalid(format!("There are no {} distinct readings of {} bits", rows, cols));
+    }
+
+    let mut seen = HashSet::new();
+    let mut readings = vec![];
+    while readings.len() < rows {
+        let reading = rng.next_u64() & ((1 << cols) - 1);
+        if seen.insert(reading) {
+            readings.push(format!("{:0width$b}", reading, width = cols));
+        }
+    }
+
+    Ok(lines(readings))
+}
+
+/// Boards of numbers below 100 and a draw of all of them, so every board wins at some point.
+fn bingo(params: &Params, rng: &mut Rng) -> Result<String, GenError> {
+    let boards = params.size(3);
+    if boards == 0 {
+        return invalid("Bingo needs at least one board".to_string());
+    }
+
+    let mut draw: Vec<usize> = (0..100).collect();
+    rng.shuffle(&mut draw);
+    let mut res = draw.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",") + "\n";
+
+    for _ in 0..boards {
+        rng.shuffle(&mut draw);
+
+        res.push('\n');
+        for row in draw[..25].chunks(5) {
+            res += &lines([row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>().join(" ")]);
+        }
+    }
+
+    Ok(res)
+}
+
+/// Horizontal and vertical lines with a share of 45 degree diagonals.
+fn vent_lines(params: &Params, rng: &mut Rng) -> Result<String, GenError> {
+    if !(0.0..=1.0).contains(&params.diagonals) {
+        return invalid(format!("Diagonal share {} is not between 0 and 1", params.diagonals));
+    }
+
+    let count = params.size(500);
+    let extent = (count * 2).clamp(10, 1000);
+
+    // Room left from a coordinate to the border in a direction
+    let room = |v: isize, d: isize| match d {
+        1 => extent as isize - 1 - v,
+        -1 => v,
+        _ => extent as isize,
+    };
+
+    Ok(lines((0..count).map(|_| loop {
+        let (x1, y1) = (rng.below(extent) as isize, rng.below(extent) as isize);
+        let (dx, dy) = match rng.chance(params.diagonals) {
+            true => *rng.choose(&[(1, 1), (1, -1), (-1, 1), (-1, -1)]),
+            false => *rng.choose(&[(1, 0), (-1, 0), (0, 1), (0, -1)]),
+        };
+
+        let max_len = room(x1, dx).min(room(y1, dy)).min(extent as isize / 3);
+        if max_len < 1 {
+            continue;
+        }
+
+        let len = rng.between(1..=max_len as usize) as isize;
+        break format!("{},{} -> {},{}", x1, y1, x1 + dx * len, y1 + dy * len);
+    })))
+}
+
+fn numbers(count: usize, range: std::ops::RangeInclusive<usize>, rng: &mut Rng) -> String {
+    (0..count).map(|_| rng.between(range.clone()).to_string()).collect::<Vec<_>>().join(",") + "\n"
+}
+
+const SEGMENTS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
+
+/// Entries of displays, each one wired at random.
+fn displays(params: &Params, rng: &mut Rng) -> String {
+    lines((0..params.size(200)).map(|_| {
+        let mut wiring: Vec<char> = "abcdefg".chars().collect();
+        rng.shuffle(&mut wiring);
+
+        let mut signals: Vec<String> = (0..10).map(|d| encode_digit(d, &wiring, rng)).collect();
+        rng.shuffle(&mut signals);
+        let outputs: Vec<String> = (0..4).map(|_| {
+            let digit = rng.below(10);
+            encode_digit(digit, &wiring, rng)
+        }).collect();
+
+        format!("{} | {}", signals.join(" "), outputs.join(" "))
+    }))
+}
+
+/// Wires lit for a digit, in no particular order.
+fn encode_digit(digit: usize, wiring: &[char], rng: &mut Rng) -> String {
+    let mut wires: Vec<char> = SEGMENTS[digit].chars().map(|s| wiring[s as usize - 'a' as usize]).collect();
+    rng.shuffle(&mut wires);
+
+    wires.into_iter().collect()
+}
+
+fn digit_grid<F: FnMut(&mut Rng) -> usize>(dims: Dims, rng: &mut Rng, mut digit: F) -> String {
+    lines((0..dims.rows).map(|_| (0..dims.cols).map(|_| char::from(b'0' + digit(rng) as u8)).collect()))
+}
+
+const OPENING: [char; 4] = ['(', '[', '{', '<'];
+const CLOSING: [char; 4] = [')', ']', '}', '>'];
+
+/// Incomplete lines, half of them corrupted by a wrong closing brace at the end.
+fn navigation(params: &Params, rng: &mut Rng) -> String {
+    lines((0..params.size(100)).map(|i| {
+        let mut line = String::new();
+        let mut open = vec![];
+
+        for _ in 0..rng.between(10..=40) {
+            match open.last() {
+                Some(&b) if rng.chance(0.4) => {
+                    line.push(CLOSING[b]);
+                    open.pop();
+                }
+                _ => {
+                    let b = rng.below(4);
+                    line.push(OPENING[b]);
+                    open.push(b);
+                }
+            }
+        }
+
+        if open.is_empty() {
+            let b = rng.below(4);
+            line.push(OPENING[b]);
+            open.push(b);
+        }
+
+        // The first line stays incomplete so there always is a middle score
+        if i > 0 && rng.chance(0.5) {
+            let expected = *open.last().unwrap();
+            line.push(CLOSING[(expected + rng.between(1..=3)) % 4]);
+        }
+
+        line
+    }))
+}
+
+/// Caves connected into a single graph, two big caves are never adjacent.
+fn caves(params: &Params, rng: &mut Rng) -> Result<String, GenError> {
+    let count = params.size(6);
+    if count == 0 {
+        return invalid("The caves need at least one cave between start and end".to_string());
+    }
+
+    let mut names: Vec<String> = vec!["start".to_string()];
+    let mut seen = HashSet::new();
+    while names.len() <= count {
+        let name: String = (0..2).map(|_| char::from(b'a' + rng.below(26) as u8)).collect();
+        let name = if rng.chance(0.3) { name.to_uppercase() } else { name };
+        if seen.insert(name.to_lowercase()) {
+            names.push(name);
+        }
+    }
+    names.push("end".to_string());
+
+    let big = |i: usize| names[i].chars().all(|c| c.is_ascii_uppercase());
+    let mut edges = HashSet::new();
+
+    // Every cave hangs off an earlier one, so all of them are reachable from the start
+    for i in 1..names.len() {
+        let candidates: Vec<usize> = (0..i).filter(|&j| !(big(i) && big(j))).collect();
+        edges.insert((*rng.choose(&candidates), i));
+    }
+
+    for _ in 0..count / 2 {
+        let (a, b) = (rng.below(names.len()), rng.below(names.len()));
+        if a != b && !(big(a) && big(b)) && !edges.contains(&(b, a)) {
+            edges.insert((a.min(b), a.max(b)));
+        }
+    }
+
+    let mut edges: Vec<(usize, usize)> = edges.into_iter().collect();
+    edges.sort();
+    rng.shuffle(&mut edges);
+
+    Ok(lines(edges.into_iter().map(|(a, b)| format!("{}-{}", names[a], names[b]))))
+}
+
+/// Two folds along each axis, the dots spread over the paper and on its far edges.
+fn origami(params: &Params, rng: &mut Rng) -> String {
+    // Paper left after folding, each fold halves the paper exactly
+    let (mut x_fold, mut y_fold) = (5, 6);
+    let mut folds = vec![];
+    for _ in 0..2 {
+        folds.push(('y', y_fold));
+        folds.push(('x', x_fold));
+        x_fold = x_fold * 2 + 1;
+        y_fold = y_fold * 2 + 1;
+    }
+    folds.reverse();
+    let (width, height) = (x_fold, y_fold);
+
+    let on_fold = |axis: char, v: usize| folds.iter().any(|&(a, f)| a == axis && f == v);
+    let coordinate = |axis: char, max: usize, rng: &mut Rng| loop {
+        let v = rng.below(max);
+        if !on_fold(axis, v) {
+            return v;
+        }
+    };
+
+    let mut dots = HashSet::new();
+    for &(axis, f) in &folds {
+        // The paper must still reach twice the fold when it gets folded
+        let dot = match axis {
+            'x' => (2 * f, coordinate('y', height, rng)),
+            _ => (coordinate('x', width, rng), 2 * f),
+        };
+        dots.insert(dot);
+    }
+    while dots.len() < folds.len() + params.size(20) {
+        dots.insert((coordinate('x', width, rng), coordinate('y', height, rng)));
+    }
+
+    let mut dots: Vec<(usize, usize)> = dots.into_iter().collect();
+    dots.sort();
+    rng.shuffle(&mut dots);
+
+    let dots = lines(dots.into_iter().map(|(x, y)| format!("{},{}", x, y)));
+    let folds = lines(folds.into_iter().map(|(axis, f)| format!("fold along {}={}", axis, f)));
+
+    dots + "\n" + &folds
+}
+
+/// Template over the alphabet and an insertion rule for every pair of its elements.
+fn polymer(params: &Params, rng: &mut Rng) -> Result<String, GenError> {
+    let mut alphabet: Vec<char> = params.alphabet.chars().collect();
+    alphabet.sort();
+    alphabet.dedup();
+    if alphabet.is_empty() || !alphabet.iter().all(|c| c.is_ascii_uppercase()) {
+        return invalid(format!("Elements must be upper case letters, got {:?}", params.alphabet));
+    }
+
+    let template: String = (0..params.size(4).max(2)).map(|_| *rng.choose(&alphabet)).collect();
+    let mut res = template + "\n\n";
+
+    for a in &alphabet {
+        for b in &alphabet {
+            writeln!(res, "{}{} -> {}", a, b, rng.choose(&alphabet)).expect("Writing to a String can't fail");
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Input;
+    use crate::solver;
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        let params = Params { size: Some(12), ..Params::default() };
+
+        for solver in solver::solvers() {
+            for seed in 0..3 {
+                let input = generate(solver.day, &params, &mut Rng::new(seed)).unwrap();
+                let puzzle = solver.parse(&Input::text(&input))
+                    .unwrap_or_else(|e| panic!("Day {} seed {}: {}\n{}", solver.day, seed, e, input));
+
+                for part in [solver.part1(&puzzle), solver.part2(&puzzle)] {
+                    match part {
+                        // Octopuses may well never flash all at once
+                        Ok(_) | Err(AdventError::NoAnswer { day: 11, part: 2 }) => {}
+                        Err(e) => panic!("Day {} seed {}: {}\n{}", solver.day, seed, e, input),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_empty_inputs() {
+        let params = Params { size: Some(0), ..Params::default() };
+
+        for solver in solver::solvers() {
+            assert!(matches!(generate(solver.day, &params, &mut Rng::new(1)), Err(GenError::InvalidParameter(_))), "Day {}", solver.day);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_input() {
+        let params = Params::default();
+        let generate = |seed| generate(12, &params, &mut Rng::new(seed)).unwrap();
+
+        assert_eq!(generate(7), generate(7));
+        assert_ne!(generate(7), generate(8));
+        assert!(!generate(7).lines().any(|l| l.chars().all(|c| c.is_ascii_uppercase() || c == '-')));
+    }
+}
@@ -0,0 +1,249 @@
+//! Answers and timings of past runs, to tell whether a refactoring changed either.
+//!
+//! `run-all --history FILE` appends a JSON line per solved part to the file, keyed by the git
+//! revision of the tree, the day, the part and a hash of the input. `history <day>` lists the
+//! runs of a day in order, comparing each with the previous run on the same input.
+//!
+//! ```text
+//! {"time":1634567890,"rev":"5ec0ef5","day":8,"part":2,"input":"9c1b6f0e3a5d7b21","answer":"61229","micros":2760000}
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::runner::{self, Report};
+use crate::AdventError;
+
+pub const DEFAULT_PATH: &str = "history.jsonl";
+
+#[derive(Parser)]
+pub struct SubCmd {
+    day: u8,
+
+    /// History written by run-all --history
+    #[clap(long, default_value = DEFAULT_PATH)]
+    file: PathBuf,
+}
+
+impl SubCmd {
+    /// The history, for watching.
+    pub fn files(&self) -> Vec<PathBuf> {
+        vec![self.file.clone()]
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("Unable to access the history {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("Invalid record on line {line} of {}: {message}", path.display())]
+    Invalid { path: PathBuf, line: usize, message: String },
+}
+
+/// Answer of a part in some run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    /// Commit the run was built from, `-dirty` with uncommitted changes
+    pub rev: String,
+    pub day: u8,
+    pub part: u8,
+    /// Hash of the input, see [`hash`]
+    pub input: String,
+    /// On a single line, as in the manifest of expected answers
+    pub answer: String,
+    pub micros: u64,
+}
+
+/// Records of the parts answered in the report, failed ones are left out.
+pub fn records(report: &Report, rev: &str, time: u64) -> Vec<Record> {
+    report.days.iter()
+        .filter_map(|d| Some((d, d.parts.as_ref().ok()?, hash(&d.input.as_ref()?.read_to_string().ok()?))))
+        .flat_map(|(d, parts, input)| parts.iter().filter_map(move |p| Some(Record {
+            time,
+            rev: rev.to_string(),
+            day: d.day,
+            part: p.part,
+            input: input.clone(),
+            answer: runner::escape(p.answer.as_ref().ok()?),
+            micros: p.elapsed.as_micros() as u64,
+        })))
+        .collect()
+}
+
+pub fn append(path: &Path, records: &[Record]) -> Result<(), HistoryError> {
+    let lines: String = records.iter()
+        .map(|r| serde_json::to_string(r).expect("Records serialize") + "\n")
+        .collect();
+
+    OpenOptions::new().create(true).append(true).open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|source| HistoryError::Io { path: path.to_path_buf(), source })
+}
+
+pub fn load(path: &Path) -> Result<Vec<Record>, HistoryError> {
+    let text = fs::read_to_string(path).map_err(|source| HistoryError::Io { path: path.to_path_buf(), source })?;
+
+    text.lines().enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line)
+            .map_err(|e| HistoryError::Invalid { path: path.to_path_buf(), line: i + 1, message: e.to_string() }))
+        .collect()
+}
+
+/// Short hash of the checked out commit, `unknown` outside of a git repository.
+pub fn revision() -> String {
+    let git = |args: &[&str]| Command::new("git").args(args).output().ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+
+    match (git(&["rev-parse", "--short", "HEAD"]), git(&["status", "--porcelain", "--untracked-files=no"])) {
+        (Some(rev), Some(changes)) if !changes.is_empty() => format!("{}-dirty", rev),
+        (Some(rev), _) => rev,
+        (None, _) => "unknown".to_string(),
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// FNV-1a of the text in hex, stable across builds unlike the hashers of std.
+pub fn hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+/// Runs of the day part by part, oldest first, each compared with the previous one on the same input.
+pub fn render(day: u8, records: &[Record]) -> String {
+    let mut res = String::new();
+    let mut parts: Vec<u8> = records.iter().filter(|r| r.day == day).map(|r| r.part).collect();
+    parts.sort_unstable();
+    parts.dedup();
+
+    for part in parts {
+        let runs: Vec<&Record> = records.iter().filter(|r| r.day == day && r.part == part).collect();
+        let rev_width = runs.iter().map(|r| r.rev.len()).max().unwrap_or(0).max(8);
+        let answer_width = runs.iter().map(|r| r.answer.len()).max().unwrap_or(0).clamp(6, 40);
+
+        if !res.is_empty() {
+            res.push('\n');
+        }
+        writeln!(res, "Day {} part {}", day, part).expect("Writing to a String can't fail");
+        writeln!(res, "  {:<16}  {:<rev_width$}  {:<8}  {:<answer_width$}  {:>10}",
+                 "date", "revision", "input", "answer", "time", rev_width = rev_width, answer_width = answer_width)
+            .expect("Writing to a String can't fail");
+
+        let mut previous: HashMap<&str, &Record> = HashMap::new();
+        for r in runs {
+            let elapsed = Duration::from_micros(r.micros);
+            let mut notes = vec![];
+            if let Some(p) = previous.get(r.input.as_str()) {
+                if p.micros > 0 {
+                    notes.push(format!("{:+.0}%", (r.micros as f64 / p.micros as f64 - 1.0) * 100.0));
+                }
+                if p.answer != r.answer {
+                    notes.push(format!("answer changed from {}", p.answer));
+                }
+            }
+
+            let line = format!("  {:<16}  {:<rev_width$}  {:<8}  {:<answer_width$}  {:>10.2?}  {}",
+                               date(r.time), r.rev, r.input.chars().take(8).collect::<String>(), r.answer, elapsed, notes.join(", "),
+                               rev_width = rev_width, answer_width = answer_width);
+            writeln!(res, "{}", line.trim_end()).expect("Writing to a String can't fail");
+            previous.insert(&r.input, r);
+        }
+    }
+
+    res
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC.
+fn date(time: u64) -> String {
+    let (days, secs) = ((time / 86400) as i64, time % 86400);
+
+    // Days to a civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", y, m, d, secs / 3600, secs % 3600 / 60)
+}
+
+pub fn main(args: SubCmd) -> Result<(), AdventError> {
+    let records = load(&args.file)?;
+
+    match render(args.day, &records) {
+        text if text.is_empty() => println!("No runs of day {} in {}", args.day, args.file.display()),
+        text => print!("{}", text),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: u64, rev: &str, input: &str, answer: &str, micros: u64) -> Record {
+        Record { time, rev: rev.to_string(), day: 1, part: 2, input: input.to_string(), answer: answer.to_string(), micros }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("advent-history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let records = vec![record(0, "abc1234", &hash("199\n200\n"), "5", 100), record(60, "abc1234", &hash(""), "#.\\n.#", 7)];
+        append(&path, &records[..1]).unwrap();
+        append(&path, &records[1..]).unwrap();
+        assert_eq!(load(&path).unwrap(), records);
+        assert_eq!(hash(""), "cbf29ce484222325");
+
+        fs::write(&path, "{\"time\":0}\n").unwrap();
+        assert!(matches!(load(&path), Err(HistoryError::Invalid { line: 1, .. })));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compares_runs_on_the_same_input() {
+        let records = vec![
+            record(1634567890, "abc1234", "00000000aaaa", "5", 2000),
+            record(1634567950, "def5678", "11111111bbbb", "7", 1000),
+            record(1634571490, "def5678-dirty", "00000000aaaa", "6", 1500),
+        ];
+
+        assert_eq!(render(1, &records), "\
+Day 1 part 2
+  date              revision       input     answer        time
+  2021-10-18 14:38  abc1234        00000000  5           2.00ms
+  2021-10-18 14:39  def5678        11111111  7           1.00ms
+  2021-10-18 15:38  def5678-dirty  00000000  6           1.50ms  -25%, answer changed from 5
+");
+        assert_eq!(render(2, &records), "");
+    }
+
+    #[test]
+    fn test_renders_edited_inputs() {
+        let records = vec![record(0, "abc1234", "ééééééééé", "5", 2000)];
+
+        assert!(render(1, &records).contains("  éééééééé  5"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Single page HTML report of a run, to be kept or shared as one file.
+//!
+//! Everything the page needs is inline: the styles and the figures, drawn as SVG.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::mem::MemStats;
+use crate::runner::{Check, DayRun, Report};
+
+const STYLE: &str = "
+body { background: #0f0f23; color: #cccccc; font-family: 'Source Code Pro', monospace; margin: 2em; }
+h1, h2 { color: #00cc00; font-weight: normal; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.8em; text-align: left; vertical-align: top; border-bottom: 1px solid #333340; }
+td.time { text-align: right; }
+pre { margin: 0; line-height: 1; }
+.ok { color: #00cc00; }
+.wrong, .failed { color: #ff5c33; }
+.unknown { color: #999999; }
+figure { display: inline-block; margin: 0 2em 2em 0; }
+figcaption { margin-top: 0.5em; }
+";
+
+/// Largest side of a figure in pixels, smaller grids get larger cells.
+const FIGURE_SIZE: usize = 500;
+
+/// The answers and timings of every day checked against the manifest, followed by the figures.
+pub fn report(report: &Report) -> String {
+    let mut html = String::new();
+    let total: Duration = report.days.iter().map(elapsed).sum();
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Advent of Code 2021</title>\n");
+    writeln!(html, "<style>{}</style>\n</head>\n<body>\n<h1>Advent of Code 2021</h1>", STYLE).expect("Writing to a String can't fail");
+    writeln!(html, "<p>{} correct, {} wrong, {} failed, solved in {:.2?}</p>",
+             correct(report), report.wrong(), report.failed(), total).expect("Writing to a String can't fail");
+
+    html.push_str("<table>\n<tr><th>Day</th><th>Puzzle</th><th>Part</th><th>Answer</th><th>Expected</th><th>Time</th><th>Memory</th></tr>\n");
+    for day in &report.days {
+        rows(&mut html, report, day);
+    }
+    html.push_str("</table>\n");
+
+    let figures: Vec<String> = report.days.iter()
+        .filter_map(|day| day.figure.as_ref().map(|f| (day.day, f)))
+        .map(|(day, figure)| {
+            let scale = (FIGURE_SIZE / figure.rows.max(figure.cols).max(1)).max(1);
+            format!("<figure>{}<figcaption>Day {}: {}</figcaption></figure>\n", figure.svg(scale), day, escape(&figure.caption))
+        })
+        .collect();
+    if !figures.is_empty() {
+        html.push_str("<h2>Pictures</h2>\n");
+        html.extend(figures);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn rows(html: &mut String, report: &Report, day: &DayRun) {
+    let name = format!("<td>{}</td><td>{}</td>", day.day, escape(day.title));
+
+    let parts = match &day.parts {
+        Ok(parts) => parts,
+        Err(e) => {
+            writeln!(html, "<tr class=\"failed\">{}<td></td><td colspan=\"4\">{}</td></tr>", name, escape(&e.to_string()))
+                .expect("Writing to a String can't fail");
+            return;
+        }
+    };
+
+    writeln!(html, "<tr>{}<td>parse</td><td></td><td></td><td class=\"time\">{:.2?}</td><td>{}</td></tr>",
+             name, day.parse_time, mem(&day.parse_mem)).expect("Writing to a String can't fail");
+
+    for part in parts {
+        let expected = report.expected.get(day.day, part.part).map_or(String::new(), |e| answer_cell(&e.replace("\\n", "\n")));
+        let (class, answer) = match &part.answer {
+            Ok(answer) => {
+                let class = match report.expected.check(day.day, part.part, answer) {
+                    Check::Unknown => "unknown",
+                    Check::Correct => "ok",
+                    Check::Wrong { .. } => "wrong",
+                };
+                (class, answer_cell(&answer.to_string()))
+            }
+            Err(e) => ("failed", escape(&e.to_string())),
+        };
+
+        writeln!(html, "<tr class=\"{}\">{}<td>{}</td><td>{}</td><td>{}</td><td class=\"time\">{:.2?}</td><td>{}</td></tr>",
+                 class, name, part.part, answer, expected, part.elapsed, mem(&part.mem)).expect("Writing to a String can't fail");
+    }
+}
+
+fn mem(mem: &Option<MemStats>) -> String {
+    mem.as_ref().map_or(String::new(), |m| escape(&m.to_string()))
+}
+
+/// Multi-line answers keep their shape.
+fn answer_cell(answer: &str) -> String {
+    match answer.trim_end().contains('\n') {
+        true => format!("<pre>{}</pre>", escape(answer.trim_end())),
+        false => escape(answer),
+    }
+}
+
+fn correct(report: &Report) -> usize {
+    report.days.iter()
+        .filter_map(|d| d.parts.as_ref().ok().map(|parts| (d.day, parts)))
+        .flat_map(|(day, parts)| parts.iter().map(move |p| (day, p)))
+        .filter(|(day, p)| matches!(&p.answer, Ok(a) if report.expected.check(*day, p.part, a) == Check::Correct))
+        .count()
+}
+
+fn elapsed(day: &DayRun) -> Duration {
+    day.parse_time + day.parts.as_ref().map_or(Duration::ZERO, |parts| parts.iter().map(|p| p.elapsed).sum())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Input;
+    use crate::runner::{run_day, Expected};
+    use crate::solver;
+
+    #[test]
+    fn test_self_contained_report() {
+        let days = vec![
+            run_day(solver::find(1).unwrap(), &Input::path("data/01_sonar_sweeps_test.txt"), true),
+            run_day(solver::find(13).unwrap(), &Input::path("data/13_folds_test.txt"), true),
+        ];
+        let expected = Expected::parse(&Input::text("1 1 7\n1 2 6\n")).unwrap();
+        let html = report(&Report { days, expected });
+
+        assert!(html.contains("<p>1 correct, 1 wrong, 0 failed, solved in"));
+        assert!(html.contains("<tr class=\"wrong\"><td>1</td><td>Sonar Sweep</td><td>2</td><td>5</td><td>6</td>"));
+        assert!(html.contains("<td><pre>#####\n#...#\n#...#\n#...#\n#####</pre></td>"));
+        assert!(html.contains("<figcaption>Day 13: Activation code</figcaption>"));
+        assert!(!html.contains("src=") && !html.contains("href="));
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
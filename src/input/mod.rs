@@ -0,0 +1,491 @@
+//! Reading puzzle inputs and parsing them into typed records.
+//!
+//! The helpers report any record which fails to parse as a [`ParseError`] pointing at the
+//! offending line and column, so all days describe malformed input the same way.
+//!
+//! Parsing is strict by default: every bad record of the input is reported and parsing fails.
+//! In lenient mode (see [`set_lenient`]) bad records are skipped instead and can be listed
+//! afterwards with [`take_skipped`].
+
+use std::cell::{Cell, RefCell};
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use nom::{Finish, IResult};
+use thiserror::Error;
+
+/**
+Source of the puzzle input: a file on disk, standard input or an in-memory string.
+
+On the command line `-` stands for stdin, anything else is treated as a path.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Path(PathBuf),
+    Stdin,
+    Text(String),
+}
+
+pub type Lines = io::Lines<Box<dyn BufRead>>;
+
+impl Input {
+    pub fn path<P: AsRef<Path>>(path: P) -> Input {
+        Input::Path(path.as_ref().to_path_buf())
+    }
+
+    pub fn stdin() -> Input {
+        Input::Stdin
+    }
+
+    pub fn text(text: &str) -> Input {
+        Input::Text(text.to_string())
+    }
+
+    pub fn reader(&self) -> io::Result<Box<dyn BufRead>> {
+        Ok(match self {
+            Input::Path(path) => {
+                let file = File::open(path).map_err(|e| io::Error::new(e.kind(), format!("Unable to read {}: {}", path.display(), e)))?;
+                Box::new(BufReader::new(file))
+            }
+            Input::Stdin => Box::new(io::stdin().lock()),
+            Input::Text(text) => Box::new(Cursor::new(text.clone().into_bytes())),
+        })
+    }
+
+    pub fn lines(&self) -> io::Result<Lines> {
+        Ok(self.reader()?.lines())
+    }
+
+    pub fn read_to_string(&self) -> io::Result<String> {
+        match self {
+            Input::Text(text) => Ok(text.clone()),
+            _ => {
+                let mut s = String::new();
+                self.reader()?.read_to_string(&mut s)?;
+                Ok(s)
+            }
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "-" => Ok(Input::stdin()),
+            _ => Ok(Input::path(s)),
+        }
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Path(path) => write!(f, "{}", path.display()),
+            Input::Stdin => write!(f, "<stdin>"),
+            Input::Text(_) => write!(f, "<text>"),
+        }
+    }
+}
+
+/**
+Record which could not be parsed. `line` and `column` count from 1, `width` is the number
+of characters at fault starting from `column`.
+
+Displayed the way compilers show diagnostics:
+
+```text
+Wrong point format
+ --> data/05_lines_test.txt:3:1
+  |
+3 | 0,9 -> 5
+  | ^^^^^^^^
+```
+ **/
+#[derive(Error, Debug)]
+pub struct ParseError {
+    pub origin: String,
+    pub line: usize,
+    pub column: usize,
+    pub width: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Error covering the whole `line` of the `input`.
+    pub fn new<M: Display>(input: &Input, line: usize, text: &str, message: M) -> ParseError {
+        ParseError {
+            origin: input.to_string(),
+            line,
+            column: 1,
+            width: text.chars().count().max(1),
+            text: text.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Narrows the error down to `width` characters starting at byte `offset` of the line.
+    pub fn at(mut self, offset: usize, width: usize) -> ParseError {
+        let offset = offset.min(self.text.len());
+
+        self.column = self.text[..offset].chars().count() + 1;
+        self.width = width.max(1);
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        writeln!(f, "{}", self.message)?;
+        writeln!(f, "{}--> {}:{}:{}", gutter, self.origin, self.line, self.column)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.text)?;
+        write!(f, "{} | {}{}", gutter, " ".repeat(self.column - 1), "^".repeat(self.width))
+    }
+}
+
+/// Every record of an input which could not be parsed.
+#[derive(Error, Debug)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, e) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n\nerror: ")?;
+            }
+            write!(f, "{}", e)?;
+        }
+
+        if self.0.len() > 1 {
+            write!(f, "\n\nerror: could not parse {} records", self.0.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    #[error(transparent)]
+    ParseErrors(#[from] ParseErrors),
+}
+
+impl From<ParseError> for InputError {
+    fn from(e: ParseError) -> Self {
+        InputError::ParseErrors(ParseErrors(vec![e]))
+    }
+}
+
+/// Bad records skipped in lenient mode while parsing a single input.
+#[derive(Debug)]
+pub struct Skipped {
+    pub origin: String,
+    pub records: usize,
+    pub errors: Vec<ParseError>,
+}
+
+impl Display for Skipped {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "skipped {} of {} records in {}", self.errors.len(), self.records, self.origin)?;
+
+        for e in &self.errors {
+            write!(f, "\n  --> {}:{}:{}: {}", e.origin, e.line, e.column, e.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+thread_local! {
+    static LENIENT: Cell<bool> = const { Cell::new(false) };
+    static SKIPPED: RefCell<Vec<Skipped>> = const { RefCell::new(vec![]) };
+}
+
+/// Switches parsing on the current thread between strict (the default) and lenient mode.
+pub fn set_lenient(lenient: bool) {
+    LENIENT.with(|l| l.set(lenient));
+}
+
+pub fn is_lenient() -> bool {
+    LENIENT.with(|l| l.get())
+}
+
+/// Bad records skipped on the current thread since the last call.
+pub fn take_skipped() -> Vec<Skipped> {
+    SKIPPED.with(|s| s.take())
+}
+
+/// Adds records skipped on another thread to the ones of this thread.
+pub fn add_skipped(skipped: Vec<Skipped>) {
+    SKIPPED.with(|s| s.borrow_mut().extend(skipped));
+}
+
+/**
+Records of an input parsed one by one. Failures are collected rather than stopping at the
+first one, on [`Records::finish`] they are either all reported or skipped, depending on the mode.
+ **/
+pub struct Records<T> {
+    origin: String,
+    parsed: Vec<T>,
+    errors: Vec<ParseError>,
+}
+
+impl<T> Records<T> {
+    pub fn new(input: &Input) -> Records<T> {
+        Records { origin: input.to_string(), parsed: vec![], errors: vec![] }
+    }
+
+    pub fn push(&mut self, record: Result<T, ParseError>) {
+        match record {
+            Ok(r) => self.parsed.push(r),
+            Err(e) => self.errors.push(e),
+        }
+    }
+
+    pub fn finish(self) -> Result<Vec<T>, InputError> {
+        if self.errors.is_empty() {
+            return Ok(self.parsed);
+        }
+
+        if !is_lenient() {
+            return Err(ParseErrors(self.errors).into());
+        }
+
+        let records = self.parsed.len() + self.errors.len();
+        SKIPPED.with(|s| s.borrow_mut().push(Skipped { origin: self.origin, records, errors: self.errors }));
+
+        Ok(self.parsed)
+    }
+}
+
+/// Results of both parsers, or the errors of both when they fail.
+pub fn join<A, B>(a: Result<A, InputError>, b: Result<B, InputError>) -> Result<(A, B), InputError> {
+    match (a, b) {
+        (Ok(a), Ok(b)) => Ok((a, b)),
+        (Err(InputError::ParseErrors(mut a)), Err(InputError::ParseErrors(b))) => {
+            a.0.extend(b.0);
+            Err(a.into())
+        }
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    }
+}
+
+/// Lines of the input separated by a blank line, `line` is the number of the first one.
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    pub line: usize,
+    pub lines: Vec<String>,
+}
+
+/// Parses every line with `f`.
+pub fn parse_lines_by<T, E, F>(input: &Input, f: F) -> Result<Vec<T>, InputError>
+    where F: Fn(&str) -> Result<T, E>, E: Display {
+    let mut records = Records::new(input);
+
+    for (i, line) in input.lines()?.enumerate() {
+        let line = line?;
+        records.push(f(&line).map_err(|e| ParseError::new(input, i + 1, &line, e)));
+    }
+
+    records.finish()
+}
+
+/// Parses every line via `FromStr`.
+pub fn parse_lines<T>(input: &Input) -> Result<Vec<T>, InputError>
+    where T: FromStr, T::Err: Display {
+    parse_lines_by(input, str::parse)
+}
+
+/// Parses every line with a nom parser which has to consume the whole line.
+pub fn parse_lines_with<T, P>(input: &Input, mut parser: P) -> Result<Vec<T>, InputError>
+    where P: FnMut(&str) -> IResult<&str, T> {
+    let mut records = Records::new(input);
+
+    for (i, line) in input.lines()?.enumerate() {
+        let line = line?;
+        records.push(parse_line_with(input, i + 1, &line, &mut parser));
+    }
+
+    records.finish()
+}
+
+/// Parses every line of the block with a nom parser which has to consume the whole line.
+pub fn parse_block_with<T, P>(input: &Input, block: &Block, mut parser: P) -> Result<Vec<T>, InputError>
+    where P: FnMut(&str) -> IResult<&str, T> {
+    let mut records = Records::new(input);
+
+    for (i, line) in block.lines.iter().enumerate() {
+        records.push(parse_line_with(input, block.line + i, line, &mut parser));
+    }
+
+    records.finish()
+}
+
+fn parse_line_with<T, P>(input: &Input, line_no: usize, line: &str, parser: &mut P) -> Result<T, ParseError>
+    where P: FnMut(&str) -> IResult<&str, T> {
+    run_parser(line, parser).map_err(|(offset, message)| {
+        ParseError::new(input, line_no, line, message).at(offset, 1)
+    })
+}
+
+/// Parses a single line of items separated by `sep`, surrounding whitespace is ignored.
+pub fn parse_list<T>(input: &Input, sep: char) -> Result<Vec<T>, InputError>
+    where T: FromStr, T::Err: Display {
+    let text = input.read_to_string()?;
+    let line = text.trim();
+
+    let mut records = Records::new(input);
+    for item in line.split(sep) {
+        let item = item.trim();
+        let offset = item.as_ptr() as usize - line.as_ptr() as usize;
+
+        records.push(item.parse().map_err(|e| ParseError::new(input, 1, line, e).at(offset, item.chars().count())));
+    }
+
+    records.finish()
+}
+
+/// Groups lines into blocks separated by one or more blank lines.
+pub fn blocks(input: &Input) -> Result<Vec<Block>, InputError> {
+    let mut res: Vec<Block> = vec![];
+    let mut cur: Option<Block> = None;
+
+    for (i, line) in input.lines()?.enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            res.extend(cur.take());
+        } else {
+            cur.get_or_insert(Block { line: i + 1, lines: vec![] }).lines.push(line);
+        }
+    }
+    res.extend(cur);
+
+    Ok(res)
+}
+
+/// Parses the whole input with a nom parser, only trailing whitespace may be left unconsumed.
+pub fn parse_with<T, P>(input: &Input, mut parser: P) -> Result<T, InputError>
+    where P: FnMut(&str) -> IResult<&str, T> {
+    let text = input.read_to_string()?;
+
+    run_parser(&text, &mut parser).map_err(|(offset, message)| {
+        let line = text[..offset].matches('\n').count();
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_text = text.lines().nth(line).unwrap_or_default();
+
+        ParseError::new(input, line + 1, line_text, message).at(offset - line_start, 1).into()
+    })
+}
+
+/// Runs the parser to completion, on failure returns the offset it stopped at.
+fn run_parser<T, P>(text: &str, parser: &mut P) -> Result<T, (usize, String)>
+    where P: FnMut(&str) -> IResult<&str, T> {
+    match parser(text).finish() {
+        Ok((rest, _)) if !rest.trim().is_empty() =>
+            Err((text.len() - rest.trim_start().len(), "unexpected trailing input".to_string())),
+        Ok((_, res)) => Ok(res),
+        Err(e) =>
+            Err((text.len() - e.input.len(), format!("unexpected input, expected {}", e.code.description().to_lowercase()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dash_is_stdin() {
+        assert_eq!("-".parse::<Input>().unwrap(), Input::Stdin);
+        assert_eq!("data/01.txt".parse::<Input>().unwrap(), Input::path("data/01.txt"));
+    }
+
+    #[test]
+    fn test_text_lines() {
+        let input = Input::text("1\n2\n3");
+        let lines: Vec<String> = input.lines().unwrap().collect::<io::Result<_>>().unwrap();
+        assert_eq!(lines, vec!["1", "2", "3"]);
+        assert_eq!(input.read_to_string().unwrap(), "1\n2\n3");
+    }
+
+    #[test]
+    fn test_parse_lines_reports_every_line() {
+        let err = parse_lines::<i32>(&Input::text("1\n2\nx\n4\ny")).unwrap_err();
+
+        match err {
+            InputError::ParseErrors(ParseErrors(errors)) => {
+                let lines: Vec<(usize, &str)> = errors.iter().map(|e| (e.line, e.text.as_str())).collect();
+                assert_eq!(lines, vec![(3, "x"), (5, "y")]);
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_lenient_skips_bad_records() {
+        set_lenient(true);
+        let parsed = parse_lines::<i32>(&Input::text("1\nx\n3\ny"));
+        let skipped = take_skipped();
+        set_lenient(false);
+
+        assert_eq!(parsed.unwrap(), vec![1, 3]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!((skipped[0].records, skipped[0].errors.len()), (4, 2));
+        assert!(take_skipped().is_empty());
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = blocks(&Input::text("a\n\nb\nc\n\n\nd\n")).unwrap();
+
+        assert_eq!(blocks, vec![
+            Block { line: 1, lines: vec!["a".to_string()] },
+            Block { line: 3, lines: vec!["b".to_string(), "c".to_string()] },
+            Block { line: 7, lines: vec!["d".to_string()] },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse_list::<i32>(&Input::text("3,4, 5\n"), ',').unwrap(), vec![3, 4, 5]);
+        assert!(parse_list::<i32>(&Input::text("3,,5"), ',').is_err());
+    }
+
+    #[test]
+    fn test_parse_with_points_at_failure() {
+        use nom::character::complete::{multispace1, u32};
+        use nom::multi::separated_list1;
+
+        let err = parse_with(&Input::text("1\n2\nx3\n"), |s| separated_list1(multispace1, u32)(s)).unwrap_err();
+
+        match err {
+            InputError::ParseErrors(ParseErrors(e)) => assert_eq!((e[0].line, e[0].column, e[0].text.as_str()), (3, 1, "x3")),
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let err = parse_list::<i32>(&Input::text("3,4,x5,6"), ',').unwrap_err();
+
+        assert_eq!(err.to_string(), [
+            "invalid digit found in string",
+            " --> <text>:1:5",
+            "  |",
+            "1 | 3,4,x5,6",
+            "  |     ^^",
+        ].join("\n"));
+    }
+}
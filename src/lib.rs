@@ -0,0 +1,228 @@
+//! Solutions for [Advent of Code 2021](https://adventofcode.com/2021).
+//!
+//! Every `day_NN` module exposes a `parse` function turning an [`input::Input`] into the
+//! day's puzzle representation and `part1`/`part2` functions computing the answers, along
+//! with the building blocks they are made of. The `main` function and `SubCmd` struct of
+//! each day back the corresponding `adventofcode dayN` command line.
+//!
+//! Answers are printed with [`answer!`], intermediate state goes through the [`log`] facade.
+//! Failures are [`AdventError`]s, their [`Category`] decides the exit code of the command.
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+pub mod animate;
+pub mod cancel;
+pub mod catalog;
+pub mod config;
+pub mod figure;
+pub mod gen;
+pub mod history;
+pub mod html;
+pub mod input;
+pub mod log;
+pub mod matrix;
+pub mod mem;
+pub mod observer;
+pub mod profile;
+pub mod reference;
+pub mod repl;
+pub mod rng;
+pub mod runner;
+pub mod serve;
+pub mod snapshot;
+pub mod solver;
+pub mod store;
+pub mod watch;
+pub mod day_01;
+pub mod day_02;
+pub mod day_03;
+pub mod day_04;
+pub mod day_05;
+pub mod day_06;
+pub mod day_07;
+pub mod day_08;
+pub mod day_09;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
+pub mod day_15;
+
+#[derive(Error, Debug)]
+pub enum AdventError {
+    #[error(transparent)]
+    InputError(#[from] input::InputError),
+
+    #[error("Day {day} part {part} has no answer for this input")]
+    NoAnswer { day: u8, part: u8 },
+
+    #[error("Grids of {rows}x{cols} are not supported")]
+    UnsupportedSize { rows: usize, cols: usize },
+
+    #[error("{0}")]
+    InvalidCommand(String),
+
+    #[error("Gave up after {0:?}")]
+    Timeout(Duration),
+
+    #[error(transparent)]
+    StoreError(#[from] store::StoreError),
+
+    #[error(transparent)]
+    ConfigError(#[from] config::ConfigError),
+
+    #[error(transparent)]
+    HistoryError(#[from] history::HistoryError),
+
+    #[error("{failed} parts failed and {wrong} answers differ from the expected ones")]
+    RunFailed { failed: usize, wrong: usize, category: Category },
+
+    #[error("Internal error, please report it: {0}")]
+    Bug(String),
+
+    #[error(transparent)]
+    GenError(#[from] gen::GenError),
+
+    #[error(transparent)]
+    Day01Error(#[from] day_01::Day1Error),
+
+    #[error(transparent)]
+    Day02Error(#[from] day_02::Day2Error),
+
+    #[error(transparent)]
+    Day03Error(#[from] day_03::Day3Error),
+
+    #[error(transparent)]
+    Day04Error(#[from] day_04::Day4Error),
+
+    #[error(transparent)]
+    Day05Error(#[from] day_05::Day5Error),
+
+    #[error(transparent)]
+    Day06Error(#[from] day_06::Day6Error),
+
+    #[error(transparent)]
+    Day07Error(#[from] day_07::Day7Error),
+
+    #[error(transparent)]
+    Day08Error(#[from] day_08::Day8Error),
+
+    #[error(transparent)]
+    Day09Error(#[from] day_09::Day9Error),
+
+    #[error(transparent)]
+    Day10Error(#[from] day_10::Day10Error),
+
+    #[error(transparent)]
+    Day11Error(#[from] day_11::DayError),
+
+    #[error(transparent)]
+    Day12Error(#[from] day_12::DayError),
+
+    #[error(transparent)]
+    Day13Error(#[from] day_13::DayError),
+
+    #[error(transparent)]
+    Day14Error(#[from] day_14::DayError),
+
+    #[error(transparent)]
+    Day15Error(#[from] day_15::DayError),
+}
+
+/**
+What went wrong, broadly, so scripts can tell bad input from broken solvers by the exit code:
+
+| Category        | Exit code | Examples                                        |
+|-----------------|-----------|-------------------------------------------------|
+| `Usage`         | 2         | unknown command argument, bad `advent.toml`     |
+| `Io`            | 3         | missing input file, failed download             |
+| `Parse`         | 4         | malformed input record                          |
+| `InvalidPuzzle` | 5         | well-formed input without an answer             |
+| `Timeout`       | 6         | solver running past `--timeout`                 |
+| `Bug`           | 101       | solver panic, wrong answer in `run-all`         |
+
+Categories are ordered by severity, a run failing in several ways reports the worst one.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Usage,
+    Io,
+    Parse,
+    InvalidPuzzle,
+    Timeout,
+    Bug,
+}
+
+impl Category {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Category::Usage => 2,
+            Category::Io => 3,
+            Category::Parse => 4,
+            Category::InvalidPuzzle => 5,
+            Category::Timeout => 6,
+            // Same as an uncaught panic
+            Category::Bug => 101,
+        }
+    }
+}
+
+impl input::InputError {
+    pub fn category(&self) -> Category {
+        match self {
+            input::InputError::IOError(_) => Category::Io,
+            input::InputError::ParseErrors(_) => Category::Parse,
+        }
+    }
+}
+
+impl matrix::MatrixError {
+    pub fn category(&self) -> Category {
+        match self {
+            matrix::MatrixError::InputError(e) => e.category(),
+            _ => Category::Parse,
+        }
+    }
+}
+
+impl AdventError {
+    pub fn category(&self) -> Category {
+        use AdventError::*;
+
+        match self {
+            InputError(e)
+            | Day01Error(day_01::Day1Error::InputError(e))
+            | Day02Error(day_02::Day2Error::InputError(e))
+            | Day03Error(day_03::Day3Error::InputError(e))
+            | Day04Error(day_04::Day4Error::InputError(e))
+            | Day05Error(day_05::Day5Error::InputError(e))
+            | Day06Error(day_06::Day6Error::InputError(e))
+            | Day07Error(day_07::Day7Error::InputError(e))
+            | Day08Error(day_08::Day8Error::InputError(e))
+            | Day10Error(day_10::Day10Error::InputError(e))
+            | Day12Error(day_12::DayError::InputError(e))
+            | Day13Error(day_13::DayError::InputError(e))
+            | Day14Error(day_14::DayError::InputError(e)) => e.category(),
+            Day09Error(day_09::Day9Error::MatrixError(e))
+            | Day11Error(day_11::DayError::MatrixError(e))
+            | Day15Error(day_15::DayError::MatrixError(e)) => e.category(),
+            NoAnswer { .. }
+            | UnsupportedSize { .. }
+            | Day04Error(day_04::Day4Error::NoWinner)
+            | Day08Error(day_08::Day8Error::NoCypher(_))
+            | Day13Error(day_13::DayError::FoldPastEdge(_)) => Category::InvalidPuzzle,
+            Day03Error(_) | Day05Error(_) | Day08Error(_) | Day10Error(_) | Day12Error(_) | Day13Error(_) => Category::Parse,
+            InvalidCommand(_) | GenError(_) => Category::Usage,
+            StoreError(_) | ConfigError(config::ConfigError::Read { .. }) => Category::Io,
+            ConfigError(_) => Category::Usage,
+            HistoryError(history::HistoryError::Io { .. }) => Category::Io,
+            HistoryError(history::HistoryError::Invalid { .. }) => Category::Parse,
+            Timeout(_) => Category::Timeout,
+            RunFailed { category, .. } => *category,
+            Bug(_) => Category::Bug,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Tiny logging facade for diagnostics printed while solving.
+//!
+//! Answers go through [`answer!`](crate::answer) and always show up on stdout, unless
+//! [`capture`]d. Everything else goes through the [`warn!`](crate::warn), [`info!`](crate::info),
+//! [`debug!`](crate::debug) and [`trace!`](crate::trace) macros to stderr, provided the global
+//! verbosity lets it through. Arguments of a disabled message are not evaluated, so logging
+//! whole grids is cheap unless asked for. Messages can be [`buffer`]ed as well, to keep the
+//! ones of concurrent solvers apart.
+
+use std::cell::RefCell;
+use std::fmt::{Arguments, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Importance of a message, from the most important to the most chatty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Warn = 1,
+    Info,
+    Debug,
+    Trace,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+thread_local! {
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+    static BUFFERED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Messages up to `level` are printed, `None` silences everything.
+pub fn set_level(level: Option<Level>) {
+    VERBOSITY.store(level.map_or(0, |l| l as u8), Ordering::Relaxed);
+}
+
+/**
+Level matching the command line flags: warnings by default, each `-v` enables one more level
+and `-q` silences everything.
+ **/
+pub fn level_from_flags(verbose: u64, quiet: bool) -> Option<Level> {
+    match (quiet, verbose) {
+        (true, _) => None,
+        (_, 0) => Some(Level::Warn),
+        (_, 1) => Some(Level::Info),
+        (_, 2) => Some(Level::Debug),
+        _ => Some(Level::Trace),
+    }
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Prints the message, use the macros instead to skip formatting of disabled messages.
+pub fn log(level: Level, args: Arguments) {
+    match level {
+        Level::Warn => emit(&format!("warning: {}\n", args)),
+        _ => emit(&format!("{}\n", args)),
+    }
+}
+
+/// Prints lines of formatted messages to stderr, or keeps them when called within [`buffer`].
+pub fn emit(messages: &str) {
+    BUFFERED.with(|b| match b.borrow_mut().as_mut() {
+        Some(out) => out.push_str(messages),
+        None => eprint!("{}", messages),
+    })
+}
+
+/// Runs `f` keeping the messages it logs on this thread, to be [`emit`]ted later.
+pub fn buffer<R>(f: impl FnOnce() -> R) -> (R, String) {
+    let outer = BUFFERED.with(|b| b.replace(Some(String::new())));
+    let res = f();
+    let out = BUFFERED.with(|b| b.replace(outer)).unwrap_or_default();
+
+    (res, out)
+}
+
+pub fn is_buffered() -> bool {
+    BUFFERED.with(|b| b.borrow().is_some())
+}
+
+/// Prints an answer line, or keeps it when called within [`capture`].
+pub fn answer(args: Arguments) {
+    CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(out) => writeln!(out, "{}", args).expect("Writing to a String can't fail"),
+        None => println!("{}", args),
+    })
+}
+
+/// Runs `f` collecting the answers it prints on this thread instead of printing them.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, String) {
+    let outer = CAPTURED.with(|c| c.replace(Some(String::new())));
+    let res = f();
+    let out = CAPTURED.with(|c| c.replace(outer)).unwrap_or_default();
+
+    (res, out)
+}
+
+/// Prints answers captured elsewhere, or keeps them when called within [`capture`].
+pub fn replay(answers: &str) {
+    for line in answers.lines() {
+        answer(format_args!("{}", line));
+    }
+}
+
+#[macro_export]
+macro_rules! answer {
+    ($($arg:tt)+) => { $crate::log::answer(format_args!($($arg)+)) };
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            $crate::log::log($level, format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(level_from_flags(0, false), Some(Level::Warn));
+        assert_eq!(level_from_flags(2, false), Some(Level::Debug));
+        assert_eq!(level_from_flags(7, false), Some(Level::Trace));
+        assert_eq!(level_from_flags(3, true), None);
+    }
+
+    #[test]
+    fn test_capture() {
+        let ((), out) = capture(|| {
+            answer(format_args!("outer {}", 1));
+            let ((), inner) = capture(|| answer(format_args!("inner")));
+            assert_eq!(inner, "inner\n");
+        });
+
+        assert_eq!(out, "outer 1\n");
+    }
+
+    #[test]
+    fn test_buffer() {
+        let ((), logs) = buffer(|| {
+            log(Level::Warn, format_args!("first"));
+            let ((), inner) = buffer(|| log(Level::Info, format_args!("inner")));
+            emit(&inner);
+        });
+
+        assert_eq!(logs, "warning: first\ninner\n");
+        assert!(!is_buffered());
+    }
+}
@@ -1,41 +1,82 @@
-#![feature(mixed_integer_ops)]
-#![allow(incomplete_features)]
-#![feature(generic_const_exprs)]
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
 
 use clap::Parser;
-use thiserror::Error;
-
-use crate::day_01::Day1SubCmd;
-use crate::day_02::Day2SubCmd;
-use crate::day_03::Day3SubCmd;
-use crate::day_04::Day4SubCmd;
-use crate::day_05::Day5SubCmd;
-use crate::day_06::Day6SubCmd;
-use crate::day_07::Day7SubCmd;
-use crate::day_08::Day8SubCmd;
-use crate::day_09::Day9SubCmd;
-use crate::day_10::Day10SubCmd;
-
-mod matrix;
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
+
+use adventofcode::*;
+use adventofcode::input::{Input, InputError};
+use adventofcode::day_01::Day1SubCmd;
+use adventofcode::day_02::Day2SubCmd;
+use adventofcode::day_03::Day3SubCmd;
+use adventofcode::day_04::Day4SubCmd;
+use adventofcode::day_05::Day5SubCmd;
+use adventofcode::day_06::Day6SubCmd;
+use adventofcode::day_07::Day7SubCmd;
+use adventofcode::day_08::Day8SubCmd;
+use adventofcode::day_09::Day9SubCmd;
+use adventofcode::day_10::Day10SubCmd;
+
+#[cfg(feature = "mem-stats")]
+#[global_allocator]
+static ALLOCATOR: mem::Counting = mem::Counting;
+
+const EXIT_CODES: &str = "EXIT CODES:
+    0      success
+    2      invalid arguments
+    3      unable to read, write or download a file
+    4      malformed input
+    5      input without an answer
+    6      solver timed out
+    101    internal error, including wrong answers in run-all";
 
 #[derive(Parser)]
-#[clap(version = "0.1", author = "Andrew Korzhuev <korzhuev@andrusha.me>")]
+#[clap(version = "0.1", author = "Andrew Korzhuev <korzhuev@andrusha.me>", after_help = EXIT_CODES)]
 struct Opts {
+    /// Skip malformed input records instead of failing, listing them as warnings
+    #[clap(long, global = true)]
+    lenient: bool,
+
+    /// Print intermediate state, repeat for more details
+    #[clap(short, long, parse(from_occurrences), global = true)]
+    verbose: u64,
+
+    /// Print only the answers and errors
+    #[clap(short, long, global = true)]
+    quiet: bool,
+
+    /// Solve again whenever an input file changes, showing how the answers changed
+    #[clap(short, long, global = true)]
+    watch: bool,
+
+    /// Give up on a solver taking longer than this many seconds
+    #[clap(long, global = true, parse(try_from_str = parse_seconds))]
+    timeout: Option<Duration>,
+
+    /// Settings of the days and their default inputs [default: advent.toml, when present]
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Override a setting of the configuration, e.g. --set day6.part2_days=18
+    #[clap(long = "set", global = true, multiple_occurrences = true, number_of_values = 1, value_name = "KEY=VALUE")]
+    set: Vec<String>,
+
+    /// Print a tree of the time spent in the phases of the solvers
+    #[clap(long, global = true)]
+    profile: bool,
+
+    /// Write the phases of the solvers as Chrome trace JSON, for chrome://tracing or Perfetto
+    #[clap(long, global = true, value_name = "FILE")]
+    trace: Option<PathBuf>,
+
+    /// Report the memory allocated while parsing and solving each part
+    #[cfg(feature = "mem-stats")]
+    #[clap(long, global = true)]
+    mem_stats: bool,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
@@ -57,61 +98,142 @@ enum SubCommand {
     Day13(day_13::SubCmd),
     Day14(day_14::SubCmd),
     Day15(day_15::SubCmd),
+    /// Write a random input for a day, the same seed always gives the same input
+    Gen(gen::SubCmd),
+    /// Answer HTTP requests for solutions, see the serve module for the API
+    Serve(serve::SubCmd),
+    /// Solve every day on its prod input, checking the answers
+    RunAll(runner::SubCmd),
+    /// Print the path of a day's input, downloading it with ADVENT_SESSION when missing
+    Fetch(store::SubCmd),
+    /// Explore puzzle inputs interactively, keeping them loaded between commands
+    Repl,
+    /// List the days with their input formats and parameters
+    List,
+    /// Show what a day takes and does, along with an example input
+    Describe(catalog::SubCmd),
+    /// Show how the answers and timings of a day changed across the runs recorded by run-all --history
+    History(history::SubCmd),
 }
 
-#[derive(Error, Debug)]
-pub enum AdventError {
-    #[error(transparent)]
-    Day01Error(#[from] day_01::Day1Error),
+fn main() {
+    let opts: Opts = Opts::parse();
+    log::set_level(log::level_from_flags(opts.verbose, opts.quiet));
+    input::set_lenient(opts.lenient);
 
-    #[error(transparent)]
-    Day02Error(#[from] day_02::Day2Error),
+    if let Err(e) = load_config(&opts) {
+        eprintln!("error: {}", e);
+        process::exit(e.category().exit_code());
+    }
 
-    #[error(transparent)]
-    Day03Error(#[from] day_03::Day3Error),
+    #[cfg(feature = "mem-stats")]
+    if opts.mem_stats {
+        mem::enable();
+    }
 
-    #[error(transparent)]
-    Day04Error(#[from] day_04::Day4Error),
+    if opts.profile || opts.trace.is_some() {
+        profile::enable();
+    }
 
-    #[error(transparent)]
-    Day05Error(#[from] day_05::Day5Error),
+    if opts.watch {
+        watch(opts);
+    }
 
-    #[error(transparent)]
-    Day06Error(#[from] day_06::Day6Error),
+    let (show_profile, trace) = (opts.profile, opts.trace.clone());
+    let (res, stats) = mem::measure(|| cancel::catch_panics(|| run(opts)));
 
-    #[error(transparent)]
-    Day07Error(#[from] day_07::Day7Error),
+    if let Some(stats) = stats {
+        eprintln!("Memory: {}", stats);
+        for (phase, stats) in mem::take_phases() {
+            eprintln!("  {:<6}  {}", phase, stats);
+        }
+    }
 
-    #[error(transparent)]
-    Day08Error(#[from] day_08::Day8Error),
+    let res = res.and(report_profile(show_profile, trace.as_deref()));
 
-    #[error(transparent)]
-    Day09Error(#[from] day_09::Day9Error),
+    for skipped in input::take_skipped() {
+        warn!("{}", skipped);
+    }
 
-    #[error(transparent)]
-    Day10Error(#[from] day_10::Day10Error),
+    if let Err(e) = res {
+        eprintln!("error: {}", e);
+        process::exit(e.category().exit_code());
+    }
+}
 
-    #[error(transparent)]
-    Day11Error(#[from] day_11::DayError),
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>().ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("Expected a number of seconds, got {:?}", s))
+}
 
-    #[error(transparent)]
-    Day12Error(#[from] day_12::DayError),
+/// Prints the spans recorded since the last report and writes them to the `trace` file.
+fn report_profile(show: bool, trace: Option<&Path>) -> Result<(), AdventError> {
+    let events = profile::take();
 
-    #[error(transparent)]
-    Day13Error(#[from] day_13::DayError),
+    if show {
+        eprint!("Profile:\n{}", profile::tree(&events));
+    }
 
-    #[error(transparent)]
-    Day14Error(#[from] day_14::DayError),
+    if let Some(path) = trace {
+        fs::write(path, profile::chrome_trace(&events))
+            .map_err(|e| io::Error::new(e.kind(), format!("Unable to write {}: {}", path.display(), e)))
+            .map_err(InputError::from)?;
+    }
 
-    #[error(transparent)]
-    Day15Error(#[from] day_15::DayError),
+    Ok(())
 }
 
+/// Reads the configuration given on the command line, or else the default one when present.
+fn load_config(opts: &Opts) -> Result<(), AdventError> {
+    let path = opts.config.clone().unwrap_or_else(|| PathBuf::from(config::DEFAULT_PATH));
+    config::install(config::Config::load(&path, opts.config.is_some(), &opts.set)?);
 
-fn main() -> Result<(), AdventError> {
-    let opts: Opts = Opts::parse();
+    Ok(())
+}
 
+fn run(opts: Opts) -> Result<(), AdventError> {
     match opts.subcmd {
+        SubCommand::RunAll(args) => runner::main(args, opts.timeout),
+        SubCommand::Gen(args) => gen::main(args),
+        SubCommand::Fetch(args) => store::main(args),
+        SubCommand::Serve(args) => serve::main(args, opts.timeout),
+        SubCommand::List => {
+            print!("{}", catalog::list());
+            Ok(())
+        }
+        SubCommand::Describe(args) => catalog::main(args),
+        SubCommand::History(args) => history::main(args),
+        SubCommand::Repl => Ok(repl::run(io::stdin().lock(), io::stdout()).map_err(InputError::from)?),
+        day => cancel::with_timeout(opts.timeout, move || solve(day)),
+    }
+}
+
+/// Day of the command and its input, when it is the command of a day.
+fn day_input(subcmd: &SubCommand) -> Option<(u8, Option<Input>)> {
+    Some(match subcmd {
+        SubCommand::Day1(args) => (1, args.input_filename.clone()),
+        SubCommand::Day2(args) => (2, args.input_filename.clone()),
+        SubCommand::Day3(args) => (3, args.input_filename.clone()),
+        SubCommand::Day4(args) => (4, args.input_filename.clone()),
+        SubCommand::Day5(args) => (5, args.input_filename.clone()),
+        SubCommand::Day6(args) => (6, args.input_filename.clone()),
+        SubCommand::Day7(args) => (7, args.input_filename.clone()),
+        SubCommand::Day8(args) => (8, args.input_filename.clone()),
+        SubCommand::Day9(args) => (9, args.input_filename.clone()),
+        SubCommand::Day10(args) => (10, args.input_filename.clone()),
+        SubCommand::Day11(args) => (11, args.input_filename.clone()),
+        SubCommand::Day12(args) => (12, args.input_filename.clone()),
+        SubCommand::Day13(args) => (13, args.input_filename.clone()),
+        SubCommand::Day14(args) => (14, args.input_filename.clone()),
+        SubCommand::Day15(args) => (15, args.input_filename.clone()),
+        _ => return None,
+    })
+}
+
+/// Runs the command of a day.
+fn solve(subcmd: SubCommand) -> Result<(), AdventError> {
+    match subcmd {
         SubCommand::Day1(args) => day_01::main(args)?,
         SubCommand::Day2(args) => day_02::main(args)?,
         SubCommand::Day3(args) => day_03::main(args)?,
@@ -127,7 +249,59 @@ fn main() -> Result<(), AdventError> {
         SubCommand::Day13(args) => day_13::main(args)?,
         SubCommand::Day14(args) => day_14::main(args)?,
         SubCommand::Day15(args) => day_15::main(args)?,
+        _ => unreachable!("Not the command of a day"),
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Re-runs the command every time one of the files it reads changes, comparing the answers.
+fn watch(opts: Opts) -> ! {
+    let files = match &opts.subcmd {
+        SubCommand::RunAll(args) => args.files().into_iter().chain(config::get().path.clone()).collect(),
+        SubCommand::Repl | SubCommand::Gen(_) | SubCommand::Serve(_) | SubCommand::Fetch(_) | SubCommand::List | SubCommand::Describe(_) => vec![],
+        SubCommand::History(args) => args.files(),
+        // Stdin can only be read once
+        day => match day_input(day) {
+            Some((_, Some(Input::Path(path)))) => iter::once(path).chain(config::get().files()).collect(),
+            Some((_, None)) => config::get().files(),
+            _ => vec![],
+        },
+    };
+
+    if files.is_empty() {
+        eprintln!("error: --watch needs input files, it can't watch stdin, the shell, gen or serve");
+        process::exit(Category::Usage.exit_code());
+    }
+
+    watch::watch(files, || {
+        // Commands consume their arguments, parse them anew for every run
+        let opts = Opts::parse();
+        load_config(&opts)?;
+
+        let (show_profile, trace) = (opts.profile, opts.trace.clone());
+        let res = match opts.subcmd {
+            SubCommand::RunAll(args) => args.report(opts.timeout).and_then(|report| {
+                println!("{}", report.render(true));
+                args.save(&report)?;
+                // Failures don't end the run, their answers are still compared with the next one
+                if let Err(e) = report.outcome() {
+                    eprintln!("error: {}", e);
+                }
+                Ok(report.render(false))
+            }),
+            _ => {
+                let (res, answers) = log::capture(|| run(opts));
+                print!("{}", answers);
+                res.map(|_| answers)
+            }
+        };
+        let res = res.and_then(|answers| report_profile(show_profile, trace.as_deref()).map(|_| answers));
+
+        for skipped in input::take_skipped() {
+            warn!("{}", skipped);
+        }
+
+        res
+    })
+}
@@ -1,13 +1,15 @@
+//! Fixed size two-dimensional grid with `N` rows and `M` columns, along with positions which
+//! are guaranteed to stay within its bounds.
+
+use std::cell::Cell;
 use std::collections::HashSet;
 use std::fmt::{Debug, Display, Formatter};
-use std::fs::File;
-use std::io;
-use std::io::BufRead;
 use std::str::FromStr;
 
-use bitvec::macros::internal::funty::IsNumber;
 use thiserror::Error;
 
+use crate::input::{self, Input, InputError, ParseError};
+
 #[derive(Debug, Clone)]
 pub struct Matrix<T, const N: usize, const M: usize> {
     m: [[T; M]; N],
@@ -28,6 +30,14 @@ impl<const N: usize, const M: usize> Pos<N, M> {
         Pos { i, j }
     }
 
+    pub fn i(&self) -> usize {
+        self.i
+    }
+
+    pub fn j(&self) -> usize {
+        self.j
+    }
+
     fn adjust(&self, diff: &Diff) -> Option<Pos<N, M>> {
         let i = self.i.checked_add_signed(diff.i);
         let j = self.j.checked_add_signed(diff.j);
@@ -82,7 +92,7 @@ impl<const N: usize, const M: usize> Iterator for IndexIterator<N, M> {
             let p = Pos { i: self.i, j: self.j };
 
             self.j += 1;
-            if self.j >= N {
+            if self.j >= M {
                 self.j = 0;
                 self.i += 1;
             }
@@ -100,6 +110,7 @@ impl<T: Copy, const N: usize, const M: usize> Matrix<T, N, M> {
         Matrix { m }
     }
 
+    /// All positions, row by row.
     pub fn index_iter(&self) -> IndexIterator<N, M> {
         IndexIterator { i: 0, j: 0 }
     }
@@ -112,17 +123,17 @@ impl<T: Copy, const N: usize, const M: usize> Matrix<T, N, M> {
         &self.m[pos.i][pos.j]
     }
 
+    /// Flood fills from `start` through direct neighbours for which `f` returns a value.
     pub fn fill<F, B>(&self, start: &Pos<N, M>, f: F) -> Matrix<Option<B>, N, M>
         where F: Fn(&T) -> Option<B>, B: Default + Copy {
         let mut m = Matrix::new(None);
 
-        let mut visit = vec![start.clone()];
+        let mut visit = vec![*start];
         let mut visited = HashSet::new();
 
-        while !visit.is_empty() {
-            let cur = visit.pop().unwrap();
+        while let Some(cur) = visit.pop() {
             if visited.contains(&cur) { continue; }
-            visited.insert(cur.clone());
+            visited.insert(cur);
 
             let x = self.get(&cur);
             if let Some(b) = f(x) {
@@ -161,6 +172,7 @@ impl<T: Copy, const N: usize, const M: usize> Matrix<T, N, M> {
         DiffIterator { i: 0, start: pos, diffs }
     }
 
+    /// Maps every element together with its direct neighbours.
     pub fn map_surroundings<F, B>(&self, f: F) -> Matrix<B, N, M>
         where F: Fn(&T, &[&T]) -> B, B: Default + Copy {
         let mut m = Matrix::new(B::default());
@@ -175,6 +187,7 @@ impl<T: Copy, const N: usize, const M: usize> Matrix<T, N, M> {
         m
     }
 
+    /// Updates every element in place.
     pub fn map<F>(&mut self, f: F)
         where F: Fn(&T) -> T {
         for pos in self.index_iter() {
@@ -213,24 +226,86 @@ impl<T: Display + Copy, const N: usize, const M: usize> Display for Matrix<T, N,
 #[derive(Error, Debug)]
 pub enum MatrixError {
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
+    InputError(#[from] InputError),
+
+    #[error("Expected a digit, got {0:?}")]
+    DigitError(char),
 
-    #[error("Unable to parse the number")]
-    ParseIntError(#[from] std::num::ParseIntError),
+    #[error("Expected {expected} columns, got {got}")]
+    RowLengthError { expected: usize, got: usize },
 }
 
-impl<T: IsNumber, const N: usize, const M: usize> Matrix<T, N, M>
-    where MatrixError: From<<T as FromStr>::Err> {
-    pub fn from_file(filename: &str) -> Result<Matrix<T, N, M>, MatrixError> {
-        let mut m = Matrix::new(T::default());
-        let lines = io::BufReader::new(File::open(filename)?).lines();
+impl<T: FromStr + Default + Copy, const N: usize, const M: usize> Matrix<T, N, M> {
+    /**
+    Reads a grid of single digits, one row per line, which has to be exactly `N` by `M`. A wrong
+    number of rows is reported at the first extra row, or past the last one when some are missing.
+     **/
+    pub fn from_input(input: &Input) -> Result<Matrix<T, N, M>, MatrixError> {
+        let number = Cell::new(0);
+        let rows = input::parse_lines_by(input, |line| {
+            number.set(number.get() + 1);
+            if line.chars().count() != M {
+                return Err(MatrixError::RowLengthError { expected: M, got: line.chars().count() });
+            }
+
+            line.chars()
+                .map(|c| c.to_string().parse().map_err(|_| MatrixError::DigitError(c)))
+                .collect::<Result<Vec<T>, _>>()
+                .map(|row| (number.get(), line.to_string(), row))
+        })?;
+
+        if rows.len() != N {
+            let (line, text) = match rows.get(N) {
+                Some((line, text, _)) => (*line, text.as_str()),
+                None => (rows.last().map_or(0, |r| r.0) + 1, ""),
+            };
+            let e = ParseError::new(input, line, text, format!("Expected {} rows, got {}", N, rows.len()));
+            return Err(InputError::from(e).into());
+        }
 
-        for (i, line) in lines.enumerate() {
-            for (j, char) in line?.chars().enumerate() {
-                m.set(&Pos::new(i, j), char.to_string().parse()?);
+        let mut m = Matrix::new(T::default());
+        for (i, (_, _, row)) in rows.into_iter().enumerate() {
+            for (j, x) in row.into_iter().enumerate() {
+                m.set(&Pos::new(i, j), x);
             }
         }
 
         Ok(m)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_iter_non_square() {
+        let m: Matrix<u8, 2, 3> = Matrix::new(0);
+        let indices: Vec<(usize, usize)> = m.index_iter().map(|p| (p.i(), p.j())).collect();
+
+        assert_eq!(indices, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_from_input() {
+        let m: Matrix<u8, 2, 3> = Matrix::from_input(&Input::text("123\n456")).unwrap();
+
+        assert_eq!(m.to_string(), "123\n456");
+        assert_eq!(m.direct_neighbours(&Pos::new(0, 0)).count(), 2);
+        assert_eq!(m.all_neighbors(&Pos::new(1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_from_input_checks_dimensions() {
+        assert!(matches!(Matrix::<u8, 2, 3>::from_input(&Input::text("123
+45")), Err(MatrixError::InputError(_))));
+        match Matrix::<u8, 2, 3>::from_input(&Input::text("123\n456\n789\n")) {
+            Err(e) => assert_eq!(e.to_string(), "Expected 2 rows, got 3\n --> <text>:3:1\n  |\n3 | 789\n  | ^^^"),
+            Ok(_) => panic!("Expected an error for the extra row"),
+        }
+        match Matrix::<u8, 2, 3>::from_input(&Input::text("123")) {
+            Err(e) => assert_eq!(e.to_string(), "Expected 2 rows, got 1\n --> <text>:2:1\n  |\n2 | \n  | ^"),
+            Ok(_) => panic!("Expected an error for the missing row"),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Memory accounting of the solvers.
+//!
+//! With the `mem-stats` feature the binary installs [`Counting`] as its global allocator, which
+//! keeps per thread tallies of every allocation. [`measure`] then reports what a closure
+//! allocated, once enabled with [`enable`], and [`phase`] keeps the tally of a phase of a day
+//! for [`take_phases`]. Without the feature nothing is counted and [`measure`] reports nothing.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Allocator counting on top of the system one.
+pub struct Counting;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    current: isize,
+    peak: isize,
+    allocated: usize,
+    allocations: usize,
+}
+
+thread_local! {
+    // No destructor and a const initializer, so using it from within the allocator is fine
+    static COUNTERS: Cell<Counters> = const { Cell::new(Counters { current: 0, peak: 0, allocated: 0, allocations: 0 }) };
+    static PHASES: RefCell<Vec<(&'static str, MemStats)>> = const { RefCell::new(Vec::new()) };
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+fn count(f: impl FnOnce(&mut Counters)) {
+    let _ = COUNTERS.try_with(|c| {
+        let mut counters = c.get();
+        f(&mut counters);
+        counters.peak = counters.peak.max(counters.current);
+        c.set(counters);
+    });
+}
+
+fn grow(c: &mut Counters, size: usize) {
+    c.current += size as isize;
+    c.allocated += size;
+    c.allocations += 1;
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(|c| grow(c, layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(|c| grow(c, layout.size()));
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        count(|c| c.current -= layout.size() as isize);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(|c| {
+            c.current -= layout.size() as isize;
+            grow(c, new_size);
+        });
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Allocations made while running a closure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemStats {
+    /// Most bytes held at once, on top of what was held before
+    pub peak: usize,
+    /// Bytes requested in total, freed ones included
+    pub allocated: usize,
+    pub allocations: usize,
+}
+
+impl Display for MemStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "peak {}, {} in {} allocations", bytes(self.peak), bytes(self.allocated), self.allocations)
+    }
+}
+
+fn bytes(n: usize) -> String {
+    match n {
+        n if n >= 1 << 20 => format!("{:.1} MiB", n as f64 / (1 << 20) as f64),
+        n if n >= 1 << 10 => format!("{:.1} KiB", n as f64 / (1 << 10) as f64),
+        n => format!("{} B", n),
+    }
+}
+
+/// Turns [`measure`] on, which only has an effect with the `mem-stats` feature.
+pub fn enable() {
+    ENABLED.store(cfg!(feature = "mem-stats"), Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Runs `f`, along with what it allocated on this thread when accounting is enabled.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Option<MemStats>) {
+    if !enabled() {
+        return (f(), None);
+    }
+
+    let before = COUNTERS.with(|c| {
+        let counters = c.get();
+        c.set(Counters { peak: counters.current, ..counters });
+        counters
+    });
+
+    let res = f();
+
+    let after = COUNTERS.with(|c| {
+        let counters = c.get();
+        // The peak of an enclosing measure must survive this one
+        c.set(Counters { peak: counters.peak.max(before.peak), ..counters });
+        counters
+    });
+    let stats = MemStats {
+        peak: (after.peak - before.current).max(0) as usize,
+        allocated: after.allocated - before.allocated,
+        allocations: after.allocations - before.allocations,
+    };
+
+    (res, Some(stats))
+}
+
+/// Counts the allocations [`measure`]d on another thread as if they were made on this one, see [`cancel::with_timeout`].
+///
+/// [`cancel::with_timeout`]: crate::cancel::with_timeout
+pub fn add(stats: Option<MemStats>) {
+    if let Some(stats) = stats {
+        count(|c| {
+            c.peak = c.peak.max(c.current + stats.peak as isize);
+            c.allocated += stats.allocated;
+            c.allocations += stats.allocations;
+        });
+    }
+}
+
+/// Runs `f` as the phase `name` of a day, see [`take_phases`].
+pub fn phase<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let (res, stats) = measure(f);
+    if let Some(stats) = stats {
+        PHASES.with(|p| p.borrow_mut().push((name, stats)));
+    }
+
+    res
+}
+
+/// Allocations of the phases run on this thread since the last call, in the order they ran.
+pub fn take_phases() -> Vec<(&'static str, MemStats)> {
+    PHASES.with(|p| p.take())
+}
+
+/// Records phases run on another thread as if they ran on this one, see [`add`].
+pub fn add_phases(phases: Vec<(&'static str, MemStats)>) {
+    PHASES.with(|p| p.borrow_mut().extend(phases));
+}
+
+#[cfg(all(test, feature = "mem-stats"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    #[test]
+    fn test_measure() {
+        enable();
+
+        let (_, stats) = measure(|| {
+            let a = vec![0u8; 1000];
+            drop(a);
+            vec![0u8; 500]
+        });
+        let stats = stats.unwrap();
+
+        assert_eq!(stats.peak, 1000);
+        assert_eq!(stats.allocated, 1500);
+        assert_eq!(stats.allocations, 2);
+    }
+
+    #[test]
+    fn test_phases() {
+        enable();
+
+        let (_, stats) = measure(|| {
+            let big = phase("parse", || vec![0u8; 1000]);
+            drop(big);
+            phase("part 1", || vec![0u8; 10].len())
+        });
+
+        let phases = take_phases();
+        assert_eq!(phases.iter().map(|(name, s)| (*name, s.peak)).collect::<Vec<_>>(), vec![("parse", 1000), ("part 1", 10)]);
+        assert!(stats.unwrap().peak >= 1000);
+        assert!(take_phases().is_empty());
+    }
+
+    #[test]
+    fn test_counts_the_worker_of_a_timeout() {
+        enable();
+
+        let (len, stats) = measure(|| crate::cancel::with_timeout(Some(Duration::from_secs(10)), || Ok(vec![0u8; 100_000].len())));
+
+        assert_eq!(len.unwrap(), 100_000);
+        assert!(stats.unwrap().peak >= 100_000);
+    }
+}
@@ -0,0 +1,78 @@
+//! Hooks into the step by step simulations.
+//!
+//! Simulations take an [`Observer`] and hand it a typed event after every step, so printing,
+//! exporting or checking intermediate state does not need changes to the solvers themselves.
+//! Closures taking a reference to the event are observers too.
+
+/// Receives the event a simulation emits after every step.
+pub trait Observer<E> {
+    fn observe(&mut self, event: &E);
+}
+
+impl<E, F: FnMut(&E)> Observer<E> for F {
+    fn observe(&mut self, event: &E) {
+        self(event)
+    }
+}
+
+/// Ignores every event, for when only the result is of interest.
+pub struct NoObserver;
+
+impl<E> Observer<E> for NoObserver {
+    fn observe(&mut self, _event: &E) {}
+}
+
+/// Keeps a copy of every event.
+#[derive(Debug)]
+pub struct Recorder<E> {
+    pub events: Vec<E>,
+}
+
+impl<E> Recorder<E> {
+    pub fn new() -> Recorder<E> {
+        Recorder { events: vec![] }
+    }
+}
+
+impl<E> Default for Recorder<E> {
+    fn default() -> Self {
+        Recorder::new()
+    }
+}
+
+impl<E: Clone> Observer<E> for Recorder<E> {
+    fn observe(&mut self, event: &E) {
+        self.events.push(event.clone());
+    }
+}
+
+/// Passes every event to both observers, in order.
+impl<E, A: Observer<E>, B: Observer<E>> Observer<E> for (A, B) {
+    fn observe(&mut self, event: &E) {
+        self.0.observe(event);
+        self.1.observe(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_to<O: Observer<usize>>(n: usize, observer: &mut O) {
+        for i in 1..=n {
+            observer.observe(&i);
+        }
+    }
+
+    #[test]
+    fn test_fan_out() {
+        let mut sum = 0;
+        let mut observers = (Recorder::new(), |i: &usize| sum += i);
+
+        count_to(3, &mut observers);
+
+        assert_eq!(observers.0.events, vec![1, 2, 3]);
+        drop(observers);
+        assert_eq!(sum, 6);
+    }
+}
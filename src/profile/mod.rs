@@ -0,0 +1,210 @@
+//! Where the time goes inside the solvers.
+//!
+//! Solvers mark their phases with `let _s = profile::span("distances");`, the span lasting until
+//! the guard is dropped. Spans opened while another is open on the same thread nest inside it.
+//! Nothing is recorded until [`enable`]d, a disabled span costs an atomic load, still spans
+//! belong around phases and not inside tight loops. [`tree`] sums the recorded spans by their
+//! place in the tree, [`chrome_trace`] lists them all for `chrome://tracing` or Perfetto.
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+pub type Name = Cow<'static, str>;
+
+/// A finished span.
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// Names of the enclosing spans, outermost first, ending with the name of this one
+    pub path: Vec<Name>,
+    /// Since the start of the process
+    pub start: Duration,
+    pub elapsed: Duration,
+    /// Sequential number of the thread the span ran on
+    pub thread: u64,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(vec![]);
+static THREADS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static OPEN: RefCell<Vec<Name>> = const { RefCell::new(vec![]) };
+    static THREAD: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn thread() -> u64 {
+    THREAD.with(|t| *t.get().get_or_insert_with(|| THREADS.fetch_add(1, Ordering::Relaxed) + 1))
+}
+
+pub fn enable() {
+    epoch();
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Guard of an open span, recorded when dropped.
+#[must_use = "The span ends when the guard is dropped"]
+pub struct Span {
+    start: Option<Instant>,
+}
+
+/// Opens a span named `name` within the spans already open on this thread.
+pub fn span<N: Into<Name>>(name: N) -> Span {
+    if !enabled() {
+        return Span { start: None };
+    }
+
+    OPEN.with(|open| open.borrow_mut().push(name.into()));
+    Span { start: Some(Instant::now()) }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let start = match self.start {
+            Some(start) => start,
+            None => return,
+        };
+
+        let elapsed = start.elapsed();
+        let path = OPEN.with(|open| {
+            let mut open = open.borrow_mut();
+            let path = open.clone();
+            open.pop();
+            path
+        });
+
+        let event = Event { path, start: start.duration_since(epoch()), elapsed, thread: thread() };
+        EVENTS.lock().expect("Profile lock poisoned").push(event);
+    }
+}
+
+/// Names of the spans open on this thread, outermost first.
+pub fn open() -> Vec<Name> {
+    OPEN.with(|open| open.borrow().clone())
+}
+
+/// Nests the spans opened from now on on this thread inside the `open` ones of another, see [`cancel::with_timeout`].
+///
+/// [`cancel::with_timeout`]: crate::cancel::with_timeout
+pub fn enter(open: Vec<Name>) {
+    OPEN.with(|o| *o.borrow_mut() = open);
+}
+
+/// The spans recorded so far, in the order they ended, leaving none behind.
+pub fn take() -> Vec<Event> {
+    std::mem::take(&mut *EVENTS.lock().expect("Profile lock poisoned"))
+}
+
+/// Total time and number of calls of every span, nested as they ran and in the order they started.
+pub fn tree(events: &[Event]) -> String {
+    let mut totals: HashMap<&[Name], (Duration, usize, Duration)> = HashMap::new();
+    for e in events {
+        let (total, calls, first) = totals.entry(&e.path).or_insert((Duration::ZERO, 0, e.start));
+        *total += e.elapsed;
+        *calls += 1;
+        *first = (*first).min(e.start);
+    }
+
+    // Parents start before their children, sorting by the start of the ancestors keeps them together
+    let mut paths: Vec<&[Name]> = totals.keys().copied().collect();
+    let key = |path: &[Name]| (1..=path.len()).map(|n| totals.get(&path[..n]).map_or(Duration::ZERO, |t| t.2)).collect::<Vec<_>>();
+    paths.sort_by_cached_key(|p| (key(p), p.to_vec()));
+
+    let width = paths.iter().map(|p| 2 * (p.len() - 1) + p[p.len() - 1].len()).max().unwrap_or(0);
+    let mut res = String::new();
+    for path in paths {
+        let (total, calls, _) = totals[path];
+        let name = format!("{}{}", "  ".repeat(path.len() - 1), path[path.len() - 1]);
+        writeln!(res, "{:<width$}  {:>10.2?}  {:>6} {}", name, total, calls, if calls == 1 { "call" } else { "calls" }, width = width)
+            .expect("Writing to a String can't fail");
+    }
+
+    res
+}
+
+/// The spans in the Trace Event Format, as complete events timed in microseconds.
+pub fn chrome_trace(events: &[Event]) -> String {
+    let events: Vec<String> = events.iter()
+        .map(|e| format!("{{\"name\":{},\"cat\":\"advent\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":{}}}",
+                         serde_json::Value::from(e.path[e.path.len() - 1].as_ref()), micros(e.start), micros(e.elapsed), e.thread))
+        .collect();
+
+    format!("{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}\n", events.join(",\n"))
+}
+
+fn micros(d: Duration) -> f64 {
+    d.as_secs_f64() * 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(path: &[&'static str], start: u64, elapsed: u64) -> Event {
+        let path = path.iter().map(|&n| Name::from(n)).collect();
+        Event { path, start: Duration::from_millis(start), elapsed: Duration::from_millis(elapsed), thread: 1 }
+    }
+
+    #[test]
+    fn test_tree() {
+        let events = [
+            event(&["parse"], 0, 1),
+            event(&["part 2", "permutations"], 2, 1),
+            event(&["part 2", "find_cypher"], 3, 2),
+            event(&["part 2", "find_cypher"], 5, 2),
+            event(&["part 2"], 1, 7),
+            event(&["part 1"], 8, 1),
+        ];
+
+        assert_eq!(tree(&events), "\
+parse               1.00ms       1 call
+part 2              7.00ms       1 call
+  permutations      1.00ms       1 call
+  find_cypher       4.00ms       2 calls
+part 1              1.00ms       1 call
+");
+        assert!(chrome_trace(&events[..1]).contains(r#"{"name":"parse","cat":"advent","ph":"X","ts":0.000,"dur":1000.000,"pid":1,"tid":1}"#));
+    }
+
+    #[test]
+    fn test_spans_nest() {
+        enable();
+        {
+            let _outer = span("outer");
+            let _inner = span(format!("inner {}", 1));
+        }
+
+        let paths: Vec<Vec<Name>> = take().into_iter().map(|e| e.path).filter(|p| p[0] == "outer").collect();
+        assert_eq!(paths, vec![vec![Name::from("outer"), Name::from("inner 1")], vec![Name::from("outer")]]);
+    }
+
+    #[test]
+    fn test_spans_nest_across_a_timeout() {
+        enable();
+        {
+            let _caller = span("caller");
+            crate::cancel::with_timeout(Some(Duration::from_secs(10)), || {
+                let _worker = span("worker");
+                Ok(())
+            }).unwrap();
+        }
+
+        let events: Vec<Event> = take().into_iter().filter(|e| e.path[0] == "caller").collect();
+        assert_eq!(events.iter().map(|e| e.path.clone()).collect::<Vec<_>>(),
+                   vec![vec![Name::from("caller"), Name::from("worker")], vec![Name::from("caller")]]);
+        assert_ne!(events[0].thread, events[1].thread);
+    }
+}
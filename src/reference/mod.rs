@@ -0,0 +1,311 @@
+//! Naive implementations to check the clever ones against.
+//!
+//! Each function here does the literal thing the puzzle describes, too slow for real inputs but
+//! simple enough to trust. [`check`] runs a solver and its reference on random inputs and, on
+//! any disagreement, shrinks the input to a minimal one that still disagrees.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Range;
+
+use crate::day_14::Insertion;
+use crate::rng::Rng;
+
+/// Number of fish after `days`, simulating every single fish.
+pub fn fish_count(timers: &[i64], days: i64) -> usize {
+    let mut fishes = timers.to_vec();
+
+    for _ in 0..days {
+        let newborn = fishes.iter().filter(|&&t| t == 0).count();
+        for t in fishes.iter_mut() {
+            *t = if *t == 0 { 6 } else { *t - 1 };
+        }
+        fishes.extend(std::iter::repeat_n(8, newborn));
+    }
+
+    fishes.len()
+}
+
+/// Element quantities after building the polymer string `steps` times.
+pub fn polymer(tmpl: &str, ins: &[Insertion], steps: usize) -> HashMap<char, usize> {
+    let rules: HashMap<(char, char), char> = ins.iter().copied().collect();
+    let mut polymer: Vec<char> = tmpl.chars().collect();
+
+    for _ in 0..steps {
+        let mut next = vec![];
+        for (i, &c) in polymer.iter().enumerate() {
+            next.push(c);
+            if let Some(mid) = polymer.get(i + 1).and_then(|&n| rules.get(&(c, n))) {
+                next.push(*mid);
+            }
+        }
+        polymer = next;
+    }
+
+    let mut tally = HashMap::new();
+    for c in polymer {
+        *tally.entry(c).or_insert(0) += 1;
+    }
+
+    tally
+}
+
+/// Least fuel to align the crabs, trying every position between the outermost crabs.
+pub fn crab_fuel<F: Fn(i32) -> i32>(positions: &[i32], cost: F) -> i32 {
+    let (min, max) = (*positions.iter().min().unwrap(), *positions.iter().max().unwrap());
+
+    (min..=max)
+        .map(|target| positions.iter().map(|&p| cost((p - target).abs())).sum())
+        .min()
+        .unwrap()
+}
+
+/// Lowest total risk from the top left to the bottom right corner, by Bellman-Ford.
+pub fn lowest_risk(grid: &[Vec<u8>]) -> usize {
+    let (rows, cols) = (grid.len(), grid[0].len());
+    let mut risk = vec![vec![usize::MAX; cols]; rows];
+    risk[0][0] = 0;
+
+    // Relaxing every edge until nothing changes, at most once per cell
+    for _ in 0..rows * cols {
+        let mut changed = false;
+
+        for i in 0..rows {
+            for j in 0..cols {
+                if risk[i][j] == usize::MAX {
+                    continue;
+                }
+
+                let neighbours = [(i.wrapping_sub(1), j), (i + 1, j), (i, j.wrapping_sub(1)), (i, j + 1)];
+                for (ni, nj) in neighbours {
+                    if ni < rows && nj < cols && risk[i][j] + (grid[ni][nj] as usize) < risk[ni][nj] {
+                        risk[ni][nj] = risk[i][j] + grid[ni][nj] as usize;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    risk[rows - 1][cols - 1]
+}
+
+/// Failing input left after shrinking, along with the reason it fails.
+#[derive(Debug)]
+pub struct Counterexample<T> {
+    pub seed: u64,
+    pub input: T,
+    pub message: String,
+}
+
+/**
+Checks `property` on an input generated for every seed. The first failing input is shrunk:
+as long as one of the smaller candidates `shrink` suggests still fails it replaces the input.
+ **/
+pub fn check<T, G, S, P>(seeds: Range<u64>, generate: G, shrink: S, property: P) -> Result<(), Counterexample<T>>
+    where T: Clone + Debug, G: Fn(&mut Rng) -> T, S: Fn(&T) -> Vec<T>, P: Fn(&T) -> Result<(), String> {
+    for seed in seeds {
+        let mut input = generate(&mut Rng::new(seed));
+
+        let mut message = match property(&input) {
+            Ok(()) => continue,
+            Err(message) => message,
+        };
+
+        'shrinking: loop {
+            for candidate in shrink(&input) {
+                if let Err(m) = property(&candidate) {
+                    input = candidate;
+                    message = m;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+
+        return Err(Counterexample { seed, input, message });
+    }
+
+    Ok(())
+}
+
+/// Shorter lists, then lists with a single item shrunk.
+pub fn shrink_vec<T: Clone, F: Fn(&T) -> Vec<T>>(items: &[T], shrink_item: F) -> Vec<Vec<T>> {
+    let mut res = vec![];
+
+    if items.len() > 1 {
+        res.push(items[..items.len() / 2].to_vec());
+        res.push(items[items.len() / 2..].to_vec());
+    }
+    for i in 0..items.len() {
+        let mut shorter = items.to_vec();
+        shorter.remove(i);
+        res.push(shorter);
+    }
+    for (i, item) in items.iter().enumerate() {
+        for smaller in shrink_item(item) {
+            let mut shrunk = items.to_vec();
+            shrunk[i] = smaller;
+            res.push(shrunk);
+        }
+    }
+
+    res
+}
+
+/// Numbers closer to `min`.
+pub fn shrink_number<N>(n: N, min: N) -> Vec<N>
+    where N: Copy + PartialOrd + std::ops::Sub<Output=N> + std::ops::Div<Output=N> + std::ops::Add<Output=N> + From<u8> {
+    if n <= min {
+        return vec![];
+    }
+
+    let half = min + (n - min) / N::from(2);
+    let mut res = vec![min];
+    if half > min && half < n {
+        res.push(half);
+    }
+    if n - N::from(1) > min {
+        res.push(n - N::from(1));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::{self, Dims, Params};
+    use crate::input::Input;
+    use crate::matrix::Matrix;
+    use crate::observer::NoObserver;
+    use crate::{day_06, day_07, day_14, day_15};
+
+    /// Input of the day from its generator, as the puzzle would give it.
+    fn generated(day: u8, params: Params, rng: &mut Rng) -> Input {
+        Input::text(&gen::generate(day, &params, rng).unwrap())
+    }
+
+    fn sized(size: usize) -> Params {
+        Params { size: Some(size), ..Params::default() }
+    }
+
+    fn agree<T: PartialEq + Debug>(solver: T, reference: T) -> Result<(), String> {
+        match solver == reference {
+            true => Ok(()),
+            false => Err(format!("solver gives {:?}, reference {:?}", solver, reference)),
+        }
+    }
+
+    fn assert_holds<T: Debug>(res: Result<(), Counterexample<T>>) {
+        if let Err(c) = res {
+            panic!("Seed {} fails with {:?}: {}", c.seed, c.input, c.message);
+        }
+    }
+
+    #[test]
+    fn test_shrinks_to_minimal_input() {
+        let res = check(0..10,
+            |rng| (0..8).map(|_| rng.below(100)).collect::<Vec<_>>(),
+            |v| shrink_vec(v, |&n| shrink_number(n, 0)),
+            |v| if v.iter().sum::<usize>() < 50 { Ok(()) } else { Err("too large".to_string()) });
+
+        assert_eq!(res.unwrap_err().input, vec![50]);
+    }
+
+    #[test]
+    fn test_day_06_against_simulation() {
+        assert_holds(check(0..50,
+            |rng| {
+                let fishes = rng.between(1..=6);
+                let timers = day_06::parse(&generated(6, sized(fishes), rng)).unwrap();
+                (timers, rng.between(0..=50) as i64)
+            },
+            |(timers, days)| {
+                let mut res: Vec<_> = shrink_vec(timers, |&t| shrink_number(t, 0)).into_iter()
+                    .filter(|t| !t.is_empty())
+                    .map(|t| (t, *days))
+                    .collect();
+                res.extend(shrink_number(*days, 0).into_iter().map(|d| (timers.clone(), d)));
+                res
+            },
+            |(timers, days)| agree(day_06::simulate(timers, *days, &day_06::Config::default(), &mut NoObserver) as usize, fish_count(timers, *days))));
+    }
+
+    #[test]
+    fn test_day_07_against_exhaustive_search() {
+        assert_holds(check(0..200,
+            |rng| {
+                let crabs = rng.between(1..=8);
+                day_07::parse(&generated(7, sized(crabs), rng)).unwrap()
+            },
+            |positions| shrink_vec(positions, |&p| shrink_number(p, 0)).into_iter().filter(|p| !p.is_empty()).collect(),
+            |positions| {
+                agree(day_07::part1(positions), crab_fuel(positions, |d| d))?;
+                agree(day_07::part2(positions), crab_fuel(positions, |d| d * (d + 1) / 2))
+            }));
+    }
+
+    #[test]
+    fn test_day_14_against_expansion() {
+        const ELEMENTS: [char; 3] = ['B', 'C', 'N'];
+
+        assert_holds(check(0..100,
+            |rng| {
+                let params = Params { size: Some(rng.between(2..=5)), alphabet: ELEMENTS.iter().collect(), ..Params::default() };
+                let (tmpl, mut ins) = day_14::parse(&generated(14, params, rng)).unwrap();
+                // Not every pair has a rule, those stay as they are
+                ins.retain(|_| rng.chance(0.7));
+                (tmpl, ins, rng.between(0..=6))
+            },
+            |(tmpl, ins, steps)| {
+                let mut res: Vec<_> = shrink_vec(ins, |_| vec![]).into_iter().map(|i| (tmpl.clone(), i, *steps)).collect();
+                if tmpl.len() > 2 {
+                    res.push((tmpl[1..].to_string(), ins.clone(), *steps));
+                    res.push((tmpl[..tmpl.len() - 1].to_string(), ins.clone(), *steps));
+                }
+                res.extend(shrink_number(*steps, 0).into_iter().map(|s| (tmpl.clone(), ins.clone(), s)));
+                res
+            },
+            |(tmpl, ins, steps)| {
+                let mut solver = day_14::polymerize(tmpl, ins, *steps);
+                solver.retain(|_, &mut n| n > 0);
+                agree(solver, polymer(tmpl, ins, *steps))
+            }));
+    }
+
+    #[test]
+    fn test_day_15_against_bellman_ford() {
+        const N: usize = 6;
+
+        assert_holds(check(0..100,
+            |rng| {
+                let params = Params { grid: Some(Dims { rows: N, cols: N }), ..Params::default() };
+                let text = gen::generate(15, &params, rng).unwrap();
+                text.lines().map(|l| l.bytes().map(|b| b - b'0').collect()).collect::<Vec<Vec<u8>>>()
+            },
+            |grid| {
+                // The size is fixed, shrink the risks towards 1
+                let mut res = vec![];
+                for i in 0..N {
+                    for j in 0..N {
+                        for smaller in shrink_number(grid[i][j], 1) {
+                            let mut g = grid.clone();
+                            g[i][j] = smaller;
+                            res.push(g);
+                        }
+                    }
+                }
+                res
+            },
+            |grid| {
+                let text: String = grid.iter().map(|r| r.iter().map(|d| d.to_string()).collect::<String>() + "\n").collect();
+                let m: Matrix<u8, N, N> = day_15::parse(&Input::text(&text)).map_err(|e| e.to_string())?;
+                agree(day_15::lowest_risk(&m), lowest_risk(grid))
+            }));
+    }
+}
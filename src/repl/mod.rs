@@ -0,0 +1,173 @@
+//! Interactive shell keeping a parsed puzzle loaded between commands.
+//!
+//! `load <day> <path>` parses an input, after which both parts and the day specific commands
+//! of its [`Solver`] can be run against it as many times as needed.
+
+use std::io::{self, BufRead, Write};
+use std::time::Instant;
+
+use crate::{cancel, mem, AdventError};
+use crate::input::Input;
+use crate::solver::{self, Puzzle, Solver};
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("load <day> <path>", "parse the input of a day, replacing the loaded one"),
+    ("part1, part2", "solve a part of the loaded puzzle"),
+    ("days", "list the days and their commands"),
+    ("help", "show this message"),
+    ("quit", "leave"),
+];
+
+pub enum Reply {
+    Output(String),
+    Quit,
+}
+
+#[derive(Default)]
+pub struct Repl {
+    loaded: Option<(&'static Solver, Puzzle)>,
+}
+
+impl Repl {
+    pub fn prompt(&self) -> String {
+        match &self.loaded {
+            Some((solver, _)) => format!("day{}> ", solver.day),
+            None => "> ".to_string(),
+        }
+    }
+
+    /// Runs a single command line.
+    pub fn eval(&mut self, line: &str) -> Result<Reply, AdventError> {
+        let line = line.trim();
+        let (cmd, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = args.trim();
+
+        if matches!(cmd, "quit" | "exit") {
+            return Ok(Reply::Quit);
+        }
+
+        // A panicking solver fails the command, not the session
+        let output = cancel::catch_panics(|| Ok(match cmd {
+            "" => String::new(),
+            "help" => self.help(),
+            "days" => days(),
+            "load" => self.load(args)?,
+            "part1" | "part2" => {
+                let (solver, puzzle) = self.loaded()?;
+                let start = Instant::now();
+                let (answer, stats) = mem::measure(|| if cmd == "part1" { solver.part1(puzzle) } else { solver.part2(puzzle) });
+                let elapsed = start.elapsed();
+
+                match stats {
+                    Some(stats) => format!("{}\n({:.2?}, {})", answer?, elapsed, stats),
+                    None => format!("{}\n({:.2?})", answer?, elapsed),
+                }
+            }
+            _ => {
+                let (solver, puzzle) = self.loaded.as_mut()
+                    .ok_or_else(|| invalid(format!("Unknown command {:?}, try help", cmd)))?;
+                let command = solver.command(cmd)
+                    .ok_or_else(|| invalid(format!("Unknown command {:?} for day {}, try help", cmd, solver.day)))?;
+
+                command.run(puzzle, args)?
+            }
+        }))?;
+
+        Ok(Reply::Output(output))
+    }
+
+    fn help(&self) -> String {
+        let mut commands: Vec<(&str, String)> = COMMANDS.iter().map(|&(u, h)| (u, h.to_string())).collect();
+
+        if let Some((solver, _)) = &self.loaded {
+            commands.extend(solver.commands.iter().map(|c| (c.usage, c.help.to_lowercase())));
+        }
+
+        commands.iter()
+            .map(|(usage, help)| format!("{:<30}{}", usage, help))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn load(&mut self, args: &str) -> Result<String, AdventError> {
+        let (day, path) = args.split_once(char::is_whitespace)
+            .ok_or_else(|| invalid("Expected load <day> <path>".to_string()))?;
+        let solver = day.parse().ok().and_then(solver::find)
+            .ok_or_else(|| invalid(format!("No such day {:?}", day)))?;
+
+        let input = match path.trim().parse::<Input>() {
+            Ok(Input::Stdin) => return Err(invalid("The shell reads stdin, load inputs from files".to_string())),
+            Ok(input) => input,
+            Err(e) => match e {},
+        };
+
+        let puzzle = solver.parse(&input)?;
+        self.loaded = Some((solver, puzzle));
+
+        Ok(format!("Loaded day {}: {} from {}", solver.day, solver.title, input))
+    }
+
+    fn loaded(&self) -> Result<(&'static Solver, &Puzzle), AdventError> {
+        match &self.loaded {
+            Some((solver, puzzle)) => Ok((solver, puzzle)),
+            None => Err(invalid("Nothing loaded, try load <day> <path>".to_string())),
+        }
+    }
+}
+
+fn days() -> String {
+    solver::solvers().iter()
+        .map(|s| {
+            let commands: Vec<&str> = s.commands.iter().map(|c| c.name).collect();
+            format!("{:>2} {:<24} {}", s.day, s.title, commands.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn invalid(message: String) -> AdventError {
+    AdventError::InvalidCommand(message)
+}
+
+/// Reads commands line by line until `quit` or the end of input, errors don't stop the shell.
+pub fn run<R: BufRead, W: Write>(input: R, mut out: W) -> io::Result<()> {
+    let mut repl = Repl::default();
+
+    write!(out, "{}", repl.prompt())?;
+    out.flush()?;
+
+    for line in input.lines() {
+        match repl.eval(&line?) {
+            Ok(Reply::Quit) => break,
+            Ok(Reply::Output(s)) if s.is_empty() => {}
+            Ok(Reply::Output(s)) => writeln!(out, "{}", s)?,
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+
+        write!(out, "{}", repl.prompt())?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let commands = "part1\nload 13 data/13_folds_test.txt\npart1\nfold x=20\nfold x=1\nfold y=7\nfold x=5\nshow\nquit\npart2\n";
+        let mut out = vec![];
+
+        run(commands.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("error: Nothing loaded"));
+        assert!(out.contains("day13> 17\n"));
+        assert!(out.contains("day13> 18 dots left\nday13> error: Folding along X(1) would put dots past the edge\n"));
+        assert!(out.contains("day13> 17 dots left\nday13> 16 dots left\n"));
+        assert!(out.contains("#####\n#...#\n#...#\n#...#\n#####\n"));
+        assert!(out.ends_with("day13> "));
+    }
+}
@@ -0,0 +1,438 @@
+//! Solves every registered day on its default input.
+//!
+//! The default input of day `N` is the one set in the [`config`](crate::config), or else its `prod`
+//! variant from the [`store`](crate::store), downloaded when missing if a session is configured. Answers are checked against a manifest of expected
+//! ones when there is one, it has a line per part:
+//!
+//! ```text
+//! # day part answer
+//! 1 1 1616
+//! 13 2 #..#\n#..#
+//! ```
+//!
+//! Multi-line answers have their line breaks written as `\n`.
+//!
+//! Days are independent, with `--jobs` several of them are solved at the same time.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+
+use crate::{cancel, config, history, html, input, log, mem, profile, AdventError, Category};
+use crate::figure::Figure;
+use crate::input::{Input, InputError, ParseError, Records};
+use crate::mem::MemStats;
+use crate::solver::{self, Answer, Solver};
+use crate::store::{Dirs, Store, PROD};
+
+#[derive(Parser)]
+pub struct SubCmd {
+    #[clap(flatten)]
+    dirs: Dirs,
+
+    /// Expected answers, checking is skipped when the file doesn't exist
+    #[clap(long, default_value = "data/answers.txt")]
+    answers: PathBuf,
+
+    /// Also write the results to this self-contained HTML page, along with pictures of some days
+    #[clap(long)]
+    report: Option<PathBuf>,
+
+    /// Append the answers and timings to this file, see the history command
+    #[clap(long, value_name = "FILE")]
+    history: Option<PathBuf>,
+
+    /// Days solved at the same time, 0 for as many as there are cores
+    #[clap(short, long, default_value = "1")]
+    jobs: usize,
+}
+
+#[derive(Debug)]
+pub struct PartRun {
+    pub part: u8,
+    pub answer: Result<Answer, AdventError>,
+    pub elapsed: Duration,
+    /// Allocations of the part, when memory accounting is on
+    pub mem: Option<MemStats>,
+}
+
+#[derive(Debug)]
+pub struct DayRun {
+    pub day: u8,
+    pub title: &'static str,
+    /// What was solved, none when the day didn't get that far
+    pub input: Option<Input>,
+    pub parse_time: Duration,
+    pub parse_mem: Option<MemStats>,
+    /// Both parts, or the reason the input could not be read
+    pub parts: Result<Vec<PartRun>, AdventError>,
+    /// Picture of the puzzle, when asked for and the day has one
+    pub figure: Option<Figure>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Check {
+    Unknown,
+    Correct,
+    Wrong { expected: String },
+}
+
+/// Expected answers by day and part.
+#[derive(Debug, Default)]
+pub struct Expected(HashMap<(u8, u8), String>);
+
+impl Expected {
+    pub fn parse(input: &Input) -> Result<Expected, InputError> {
+        let mut records = Records::new(input);
+
+        for (i, line) in input.lines()?.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            records.push(parse_expected(&line).map_err(|e| ParseError::new(input, i + 1, &line, e)));
+        }
+
+        Ok(Expected(records.finish()?.into_iter().collect()))
+    }
+
+    /// Expected answer as written in the manifest.
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn check(&self, day: u8, part: u8, answer: &Answer) -> Check {
+        match self.0.get(&(day, part)) {
+            None => Check::Unknown,
+            Some(expected) if *expected == escape(answer) => Check::Correct,
+            Some(expected) => Check::Wrong { expected: expected.clone() },
+        }
+    }
+}
+
+fn parse_expected(line: &str) -> Result<((u8, u8), String), String> {
+    let mut fields = line.trim().splitn(3, ' ');
+    let mut number = |name| fields.next()
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(|| format!("Expected a {} number", name));
+
+    let key = (number("day")?, number("part")?);
+    match fields.next() {
+        Some(answer) if !answer.trim().is_empty() => Ok((key, answer.trim().to_string())),
+        _ => Err("Expected an answer after the day and part".to_string()),
+    }
+}
+
+/// Answer on a single line, as written in the manifest.
+pub(crate) fn escape(answer: &Answer) -> String {
+    answer.to_string().trim_end().replace('\n', "\\n")
+}
+
+/// Parses the input and solves both parts, timing each step. The figure is drawn afterwards.
+pub fn run_day(solver: &Solver, input: &Input, figure: bool) -> DayRun {
+    let _s = profile::span(format!("day {}", solver.day));
+    let start = Instant::now();
+    let (puzzle, parse_mem) = mem::measure(|| {
+        let _s = profile::span("parse");
+        solver.parse(input)
+    });
+    let parse_time = start.elapsed();
+
+    let (parts, figure) = match puzzle {
+        Ok(puzzle) => {
+            let mut parts = vec![run_part(1, || solver.part1(&puzzle))];
+            if solver.has_part2() {
+                parts.push(run_part(2, || solver.part2(&puzzle)));
+            }
+            let figure = figure.then(|| {
+                let _s = profile::span("figure");
+                solver.figure(&puzzle)
+            });
+            (Ok(parts), figure.flatten())
+        }
+        Err(e) => (Err(e), None),
+    };
+
+    DayRun { day: solver.day, title: solver.title, input: Some(input.clone()), parse_time, parse_mem, parts, figure }
+}
+
+fn run_part<F: FnOnce() -> Result<Answer, AdventError>>(part: u8, solve: F) -> PartRun {
+    let _s = profile::span(if part == 1 { "part 1" } else { "part 2" });
+    let start = Instant::now();
+    let (answer, mem) = mem::measure(solve);
+
+    PartRun { part, answer, elapsed: start.elapsed(), mem }
+}
+
+/// Results of every day along with their expected answers.
+pub struct Report {
+    pub days: Vec<DayRun>,
+    pub expected: Expected,
+}
+
+impl Report {
+    /// Answers differing from the expected ones.
+    pub fn wrong(&self) -> usize {
+        self.checks().filter(|c| matches!(c, Check::Wrong { .. })).count()
+    }
+
+    /// Parts without an answer, a day which could not be parsed counts for both.
+    pub fn failed(&self) -> usize {
+        self.days.iter()
+            .map(|d| match &d.parts {
+                Ok(parts) => parts.iter().filter(|p| p.answer.is_err()).count(),
+                Err(_) => 2,
+            })
+            .sum()
+    }
+
+    /// Fails with [`AdventError::RunFailed`] when a part failed or got a wrong answer.
+    pub fn outcome(&self) -> Result<(), AdventError> {
+        match self.category() {
+            None => Ok(()),
+            Some(category) => Err(AdventError::RunFailed { failed: self.failed(), wrong: self.wrong(), category }),
+        }
+    }
+
+    /// Worst category among the failures, wrong answers count as bugs.
+    pub fn category(&self) -> Option<Category> {
+        let errors = self.days.iter().flat_map(|d| match &d.parts {
+            Ok(parts) => parts.iter().filter_map(|p| p.answer.as_ref().err()).collect(),
+            Err(e) => vec![e],
+        });
+        let wrong = Some(Category::Bug).filter(|_| self.wrong() > 0);
+
+        errors.map(AdventError::category).chain(wrong).max()
+    }
+
+    fn checks(&self) -> impl Iterator<Item=Check> + '_ {
+        self.days.iter()
+            .filter_map(|d| d.parts.as_ref().ok().map(|parts| (d.day, parts)))
+            .flat_map(|(day, parts)| parts.iter().map(move |p| (day, p)))
+            .filter_map(|(day, p)| p.answer.as_ref().ok().map(|a| self.expected.check(day, p.part, a)))
+    }
+
+    /**
+    Table of the answers, the timings and memory use are left out when `timings` is off so that
+    the output of two runs can be compared.
+     **/
+    pub fn render(&self, timings: bool) -> String {
+        let time = |d: Duration, mem: &Option<MemStats>| match (timings, mem) {
+            (false, _) => String::new(),
+            (true, None) => format!("{:>10.2?}", d),
+            (true, Some(mem)) => format!("{:>10.2?}  {}", d, mem),
+        };
+        let mut lines = vec![];
+
+        for day in &self.days {
+            let name = format!("{:>2} {:<24}", day.day, day.title);
+
+            let parts = match &day.parts {
+                Ok(parts) => parts,
+                Err(e) => {
+                    lines.push(format!("{} error: {}", name, e));
+                    continue;
+                }
+            };
+            if timings {
+                lines.push(format!("{} parse  {:<16}{}", name, "", time(day.parse_time, &day.parse_mem)));
+            }
+
+            for part in parts {
+                let answer = match &part.answer {
+                    Ok(answer) => answer,
+                    Err(e) => {
+                        lines.push(format!("{} part {} error: {}", name, part.part, e));
+                        continue;
+                    }
+                };
+
+                let check = match self.expected.check(day.day, part.part, answer) {
+                    Check::Unknown => String::new(),
+                    Check::Correct => "  ok".to_string(),
+                    Check::Wrong { expected } => format!("  WRONG, expected {}", expected),
+                };
+
+                // Multi-line answers go below their row
+                let (value, below) = match answer {
+                    Answer::Text(text) if text.contains('\n') => (String::new(), text.lines().collect()),
+                    _ => (answer.to_string(), vec![]),
+                };
+
+                lines.push(format!("{} part {} {:<16}{}{}", name, part.part, value, time(part.elapsed, &part.mem), check));
+                lines.extend(below.iter().map(|l| format!("    {}", l)));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl SubCmd {
+    /// Files the run reads, the manifest included.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let store = Store::from_dirs(&self.dirs);
+        let config = config::get();
+
+        solver::solvers().iter()
+            .filter_map(|s| config.input(s.day).map(Path::to_path_buf).or_else(|| store.find(s.day, PROD)))
+            .chain([self.answers.clone()])
+            .collect()
+    }
+
+    /// Writes the HTML page and appends to the history, those which were asked for.
+    pub fn save(&self, report: &Report) -> Result<(), AdventError> {
+        if let Some(path) = &self.report {
+            fs::write(path, html::report(report))
+                .map_err(|e| InputError::from(io::Error::new(e.kind(), format!("Unable to write {}: {}", path.display(), e))))?;
+        }
+
+        if let Some(path) = &self.history {
+            history::append(path, &history::records(report, &history::revision(), history::now()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs every day, giving up on the ones taking longer than `timeout`.
+    pub fn report(&self, timeout: Option<Duration>) -> Result<Report, AdventError> {
+        let expected = match self.answers.exists() {
+            true => Expected::parse(&Input::path(&self.answers))?,
+            false => Expected::default(),
+        };
+        let store = Store::from_dirs(&self.dirs);
+        let config = config::get();
+        let figures = self.report.is_some();
+
+        let run = |solver: &'static Solver| {
+            let input = match config.input(solver.day) {
+                Some(path) => Ok(path.to_path_buf()),
+                None => store.resolve(solver.day, PROD),
+            };
+            let run = match input {
+                Ok(path) => cancel::with_timeout(timeout, move || Ok(run_day(solver, &Input::path(path), figures))),
+                Err(e) => Err(e.into()),
+            };
+
+            run.unwrap_or_else(|e| DayRun {
+                day: solver.day,
+                title: solver.title,
+                input: None,
+                parse_time: Duration::ZERO,
+                parse_mem: None,
+                parts: Err(e),
+                figure: None,
+            })
+        };
+
+        let jobs = match self.jobs {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            jobs => jobs,
+        };
+        let days = match jobs {
+            1 => solver::solvers().iter().map(run).collect(),
+            jobs => parallel(solver::solvers(), jobs, run),
+        };
+
+        Ok(Report { days, expected })
+    }
+}
+
+/**
+Runs `f` for every solver on `jobs` threads, the results come in the order of the solvers. The
+log messages and skipped records of each run are held back and replayed in that order too, so
+the output of days solved at the same time never interleaves.
+ **/
+fn parallel<R, F>(solvers: &'static [Solver], jobs: usize, f: F) -> Vec<R>
+    where R: Send, F: Fn(&'static Solver) -> R + Sync {
+    let next = AtomicUsize::new(0);
+    let done = Mutex::new(Vec::with_capacity(solvers.len()));
+    let lenient = input::is_lenient();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(solvers.len()) {
+            thread::Builder::new()
+                .name("run-all".to_string())
+                .stack_size(cancel::STACK_SIZE)
+                .spawn_scoped(scope, || {
+                    input::set_lenient(lenient);
+
+                    while let Some(solver) = solvers.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let (res, logs) = log::buffer(|| f(solver));
+                        done.lock().expect("A worker panicked").push((solver.day, res, logs, input::take_skipped()));
+                    }
+                })
+                .expect("Unable to start a worker thread");
+        }
+    });
+
+    let mut done = done.into_inner().expect("A worker panicked");
+    done.sort_by_key(|(day, ..)| *day);
+
+    done.into_iter()
+        .map(|(_, res, logs, skipped)| {
+            log::emit(&logs);
+            input::add_skipped(skipped);
+            res
+        })
+        .collect()
+}
+
+pub fn main(args: SubCmd, timeout: Option<Duration>) -> Result<(), AdventError> {
+    let report = args.report(timeout)?;
+    println!("{}", report.render(true));
+    args.save(&report)?;
+
+    report.outcome()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_answers() {
+        let expected = Expected::parse(&Input::text("# day part answer\n1 1 7\n\n13 2 #.\\n.#\n")).unwrap();
+
+        assert_eq!(expected.check(1, 1, &Answer::Number(7)), Check::Correct);
+        assert_eq!(expected.check(1, 2, &Answer::Number(5)), Check::Unknown);
+        assert_eq!(expected.check(13, 2, &Answer::Text("#.\n.#\n".to_string())), Check::Correct);
+        assert_eq!(expected.check(1, 1, &Answer::Number(8)), Check::Wrong { expected: "7".to_string() });
+
+        assert!(Expected::parse(&Input::text("1 x 7\n")).is_err());
+    }
+
+    #[test]
+    fn test_parallel_keeps_day_order() {
+        let (days, logs) = log::buffer(|| parallel(solver::solvers(), 4, |s| {
+            // Later days finish first
+            thread::sleep(Duration::from_millis(20 - s.day as u64));
+            log::log(log::Level::Warn, format_args!("day {}", s.day));
+            s.day
+        }));
+
+        assert_eq!(days, (1..=15).collect::<Vec<_>>());
+        assert_eq!(logs, (1..=15).map(|d| format!("warning: day {}\n", d)).collect::<String>());
+    }
+
+    #[test]
+    fn test_worst_category() {
+        let run = |text: &str| run_day(solver::find(1).unwrap(), &Input::text(text), false);
+        let mut report = Report { days: vec![run("1\n2\n")], expected: Expected::default() };
+        assert_eq!(report.category(), None);
+
+        report.days.push(run("1\nx\n"));
+        assert_eq!(report.category(), Some(Category::Parse));
+
+        report.expected = Expected::parse(&Input::text("1 1 7\n")).unwrap();
+        assert_eq!(report.category(), Some(Category::Bug));
+    }
+}
@@ -0,0 +1,384 @@
+//! Solvers over HTTP, for tools which would rather not start a process per puzzle.
+//!
+//! A minimal HTTP/1.1 server on [`std::net`], every connection carries a single request:
+//!
+//! - `GET /days` lists the registered solvers,
+//! - `POST /day/{n}/part/{p}` solves a part for the puzzle input in the request body.
+//!
+//! Responses are JSON, errors come as `{"error": "..."}` with a matching status code.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+
+use crate::input::{self, Input, InputError};
+use crate::solver::{self, Answer};
+use crate::{cancel, info, warn, AdventError};
+
+/// Inputs are a few dozen kilobytes, anything much larger is a mistake.
+const MAX_BODY: usize = 16 << 20;
+
+/// Clients get this long to send each part of their request, a silent one would hold its thread forever.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections served at once, the ones beyond are turned away.
+const MAX_CONNECTIONS: usize = 64;
+
+#[derive(Parser)]
+pub struct SubCmd {
+    /// Port to listen on, 0 picks a free one
+    #[clap(long, default_value = "8080")]
+    port: u16,
+
+    /// Address to listen on, only this machine by default
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Response {
+        Response { status, body }
+    }
+
+    fn error<E: ToString>(status: u16, error: E) -> Response {
+        Response::json(status, format!("{{\"error\":{}}}", json_string(&error.to_string())))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+pub struct Server {
+    listener: TcpListener,
+    timeout: Option<Duration>,
+    read_timeout: Duration,
+    max_connections: usize,
+}
+
+/// Place of a connection among the [`MAX_CONNECTIONS`], freed when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Server {
+    /// Listens on the address, solvers taking longer than `timeout` are given up on.
+    pub fn bind<A: ToSocketAddrs>(addr: A, timeout: Option<Duration>) -> io::Result<Server> {
+        Ok(Server { listener: TcpListener::bind(addr)?, timeout, read_timeout: READ_TIMEOUT, max_connections: MAX_CONNECTIONS })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves connections until the process ends, each on its own thread with the stack of the solvers.
+    pub fn run(&self) -> io::Result<()> {
+        let lenient = input::is_lenient();
+        let active = Arc::new(AtomicUsize::new(0));
+
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let timeout = self.timeout;
+
+            if active.fetch_add(1, Ordering::Relaxed) >= self.max_connections {
+                active.fetch_sub(1, Ordering::Relaxed);
+                if let Err(e) = turn_away(stream) {
+                    warn!("Unable to turn a connection away: {}", e);
+                }
+                continue;
+            }
+            let slot = Slot(active.clone());
+            stream.set_read_timeout(Some(self.read_timeout))?;
+
+            thread::Builder::new()
+                .stack_size(cancel::STACK_SIZE)
+                .spawn(move || {
+                    let _slot = slot;
+                    input::set_lenient(lenient);
+                    if let Err(e) = handle(stream, timeout) {
+                        warn!("Unable to answer a request: {}", e);
+                    }
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+fn handle(stream: TcpStream, timeout: Option<Duration>) -> io::Result<()> {
+    let start = Instant::now();
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let (request, response) = match read_request(&mut reader) {
+        Ok(request) => {
+            let response = route(&request.method, &request.path, &request.body, timeout);
+            (format!("{} {}", request.method, request.path), response)
+        }
+        Err(response) => ("Malformed request".to_string(), response),
+    };
+
+    info!("{} {} ({:.2?})", request, response.status, start.elapsed());
+    for skipped in input::take_skipped() {
+        warn!("{}", skipped);
+    }
+
+    respond(stream, &response)
+}
+
+/// Answers 503 without reading the request, which is drained so that closing doesn't reset the connection.
+fn turn_away(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_millis(100)))?;
+    respond(stream.try_clone()?, &Response::error(503, "Too many connections, try again later"))?;
+    stream.shutdown(Shutdown::Write)?;
+    let _ = io::copy(&mut &stream, &mut io::sink());
+
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, response: &Response) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           response.status, response.reason(), response.body.len(), response.body)?;
+    stream.flush()
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Result<Request, Response> {
+    let bad = |e: &dyn ToString| Response::error(400, e.to_string());
+    let failed = |e: io::Error| match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Response::error(408, "Timed out waiting for the request"),
+        _ => bad(&e),
+    };
+
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(failed)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/1.") => (method.to_string(), path.to_string()),
+        _ => return Err(bad(&format!("Malformed request line {:?}", line.trim_end()))),
+    };
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(failed)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().map_err(|_| bad(&format!("Malformed Content-Length {:?}", value.trim())))?;
+            }
+        }
+    }
+
+    if length > MAX_BODY {
+        return Err(Response::error(413, format!("Inputs are limited to {} bytes", MAX_BODY)));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(failed)?;
+    let body = String::from_utf8(body).map_err(|_| bad(&"The input is not UTF-8"))?;
+
+    Ok(Request { method, path, body })
+}
+
+/// Response to a request, separate from the connection handling so it can be tested directly.
+pub fn route(method: &str, path: &str, body: &str, timeout: Option<Duration>) -> Response {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["days"]) => Response::json(200, days()),
+        ("POST", ["day", day, "part", part]) => {
+            match (day.parse().ok().and_then(solver::find), part.parse::<u8>()) {
+                (Some(solver), Ok(part @ (1 | 2))) => solve(solver, part, body.to_string(), timeout),
+                _ => Response::error(404, format!("No solver for day {} part {}", day, part)),
+            }
+        }
+        (_, ["days"]) | (_, ["day", _, "part", _]) => Response::error(405, format!("{} is not supported here", method)),
+        _ => Response::error(404, format!("Nothing at {}", path)),
+    }
+}
+
+fn days() -> String {
+    let days: Vec<String> = solver::solvers().iter()
+        .map(|s| {
+            let commands: Vec<String> = s.commands.iter().map(|c| json_string(c.name)).collect();
+            format!("{{\"day\":{},\"title\":{},\"commands\":[{}]}}", s.day, json_string(s.title), commands.join(","))
+        })
+        .collect();
+
+    format!("[{}]", days.join(","))
+}
+
+fn solve(solver: &'static solver::Solver, part: u8, body: String, timeout: Option<Duration>) -> Response {
+    let res = cancel::with_timeout(timeout, move || Ok(solve_timed(solver, part, &body)));
+
+    match res {
+        Ok(Ok((answer, parse_time, solve_time))) => {
+            let answer = match answer {
+                Answer::Number(n) => n.to_string(),
+                Answer::Text(s) => json_string(&s),
+            };
+
+            Response::json(200, format!("{{\"day\":{},\"part\":{},\"answer\":{},\"parse_ms\":{:.3},\"solve_ms\":{:.3}}}",
+                                        solver.day, part, answer, millis(parse_time), millis(solve_time)))
+        }
+        Ok(Err((status, e))) => Response::error(status, e),
+        Err(e @ AdventError::Timeout(_)) => Response::error(503, e),
+        Err(e) => Response::error(500, e),
+    }
+}
+
+/// Answer along with the parse and solve times, or the error with its status code.
+fn solve_timed(solver: &solver::Solver, part: u8, body: &str) -> Result<(Answer, Duration, Duration), (u16, AdventError)> {
+    let start = Instant::now();
+    let puzzle = solver.parse(&Input::text(body)).map_err(|e| (400, e))?;
+    let parse_time = start.elapsed();
+
+    let start = Instant::now();
+    let answer = match part {
+        1 => solver.part1(&puzzle),
+        _ => solver.part2(&puzzle),
+    };
+
+    answer.map(|a| (a, parse_time, start.elapsed())).map_err(|e| (422, e))
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c < ' ' => write!(res, "\\u{:04x}", c as u32).expect("Writing to a String can't fail"),
+            c => res.push(c),
+        }
+    }
+
+    res.push('"');
+    res
+}
+
+pub fn main(args: SubCmd, timeout: Option<Duration>) -> Result<(), AdventError> {
+    let server = Server::bind((args.host.as_str(), args.port), timeout).map_err(InputError::from)?;
+    eprintln!("Listening on http://{}", server.local_addr().map_err(InputError::from)?);
+
+    Ok(server.run().map_err(InputError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn request(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serves_localhost() {
+        let server = Server::bind("127.0.0.1:0", Some(Duration::from_secs(10))).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        let days = request(addr, "GET /days HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(days.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(days.contains(r#"{"day":13,"title":"Transparent Origami","commands":["fold","show"]}"#));
+
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        let solved = request(addr, &format!("POST /day/1/part/2 HTTP/1.1\r\ncontent-length: {}\r\n\r\n{}", input.len(), input));
+        assert!(solved.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(solved.contains(r#"{"day":1,"part":2,"answer":5,"parse_ms":"#));
+
+        let malformed = request(addr, "POST /day/1/part/1 HTTP/1.1\r\nContent-Length: 4\r\n\r\n1\nx\n");
+        assert!(malformed.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[test]
+    fn test_solves_large_grids_without_timeout() {
+        let server = Server::bind("127.0.0.1:0", None).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        // Without a timeout the solver runs on the connection thread, the tiled map needs its large stack
+        let input = std::fs::read_to_string("data/15_path_prod.txt").unwrap();
+        let solved = request(addr, &format!("POST /day/15/part/2 HTTP/1.1\r\ncontent-length: {}\r\n\r\n{}", input.len(), input));
+        assert!(solved.starts_with("HTTP/1.1 200 OK\r\n"), "{}", solved);
+    }
+
+    #[test]
+    fn test_turns_away_silent_and_excess_connections() {
+        let mut server = Server::bind("127.0.0.1:0", None).unwrap();
+        server.read_timeout = Duration::from_millis(200);
+        server.max_connections = 1;
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        let mut silent = TcpStream::connect(addr).unwrap();
+        let busy = request(addr, "GET /days HTTP/1.1\r\n\r\n");
+        assert!(busy.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{}", busy);
+
+        let mut timed_out = String::new();
+        silent.read_to_string(&mut timed_out).unwrap();
+        assert!(timed_out.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{}", timed_out);
+    }
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(route("POST", "/day/16/part/1", "", None).status, 404);
+        assert_eq!(route("GET", "/day/1/part/1", "", None).status, 405);
+        assert_eq!(route("POST", "/day/1/part/3", "", None).status, 404);
+        assert_eq!(route("DELETE", "/days", "", None).status, 405);
+        assert_eq!(route("POST", "/day/9/part/1", "123\n456\n", None),
+                   Response::error(400, "Grids of 2x3 are not supported"));
+        assert_eq!(json_string("a\"b\n\u{1}"), r#""a\"b\n\u0001""#);
+    }
+}
@@ -0,0 +1,95 @@
+//! Snapshot tests of the intermediate outputs of the solvers: grids, drawings, path listings.
+//!
+//! [`assert_snapshot`] compares an output with the one checked in as `snapshots/<name>.snap` and
+//! fails with the lines that differ. Once a change is intended, `UPDATE_SNAPSHOTS=1 cargo test`
+//! accepts it by writing the new outputs, which then show up in the diff of the commit. Snapshots
+//! which don't exist yet are written the same way.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::watch;
+
+pub const DIR: &str = "snapshots";
+/// Set to `1` to accept the outputs as the new snapshots.
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("No snapshot {}, run with {UPDATE_VAR}=1 to accept this output:\n{actual}", path.display())]
+    Missing { path: PathBuf, actual: String },
+
+    #[error("Output differs from the snapshot {}, run with {UPDATE_VAR}=1 to accept it:\n{}", path.display(), changes.join("\n"))]
+    Changed { path: PathBuf, changes: Vec<String> },
+
+    #[error("Unable to access the snapshot {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/**
+Compares `actual` with the snapshot `name` in `dir`, or writes it there when `update`. Returns
+whether the snapshot was written, trailing newlines are not significant.
+ **/
+pub fn check(dir: &Path, name: &str, actual: &str, update: bool) -> Result<bool, SnapshotError> {
+    let path = dir.join(format!("{}.snap", name));
+    let actual = format!("{}\n", actual.trim_end_matches('\n'));
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(source) => return Err(SnapshotError::Io { path, source }),
+    };
+
+    match expected {
+        Some(expected) if expected.trim_end_matches('\n') == actual.trim_end_matches('\n') => Ok(false),
+        _ if update => {
+            fs::create_dir_all(dir)
+                .and_then(|_| fs::write(&path, actual))
+                .map_err(|source| SnapshotError::Io { path, source })?;
+            Ok(true)
+        }
+        Some(expected) => Err(SnapshotError::Changed { changes: watch::diff(&expected, &actual), path }),
+        None => Err(SnapshotError::Missing { path, actual }),
+    }
+}
+
+/// Fails the test unless `actual` matches the checked-in snapshot `name`, see the module docs.
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let update = env::var(UPDATE_VAR).is_ok_and(|v| v == "1");
+
+    match check(Path::new(DIR), name, actual, update) {
+        Ok(true) => eprintln!("Updated the snapshot {}", name),
+        Ok(false) => {}
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_changes() {
+        let dir = env::temp_dir().join(format!("advent-snapshots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(matches!(check(&dir, "grid", "12\n34\n", false), Err(SnapshotError::Missing { .. })));
+        assert!(check(&dir, "grid", "12\n34\n", true).unwrap());
+        assert_eq!(fs::read_to_string(dir.join("grid.snap")).unwrap(), "12\n34\n");
+        assert!(!check(&dir, "grid", "12\n34", false).unwrap());
+
+        match check(&dir, "grid", "12\n43\n", false) {
+            Err(e @ SnapshotError::Changed { .. }) => assert!(e.to_string().ends_with(":\n- 34\n+ 43")),
+            res => panic!("Expected a change, got {:?}", res),
+        }
+        assert!(check(&dir, "grid", "12\n43\n", true).unwrap());
+        assert!(!check(&dir, "grid", "12\n43\n", false).unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,520 @@
+//! Registry of all days behind a single, type-erased interface.
+//!
+//! Every [`Solver`] parses an [`Input`] into an opaque [`Puzzle`] and computes the answers of
+//! both parts from it, which lets tools drive any day without knowing its types. Days may also
+//! register extra [`Command`]s operating on the parsed puzzle, for interactive exploration, and
+//! a [`Figure`] picturing it. The metadata of a solver, its input format and parameters, backs
+//! the `list` and `describe` commands.
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::config;
+use crate::figure::Figure;
+use crate::input::{Input, InputError};
+use crate::matrix::{Matrix, Pos};
+use crate::observer::NoObserver;
+use crate::{AdventError, day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12, day_13, day_14, day_15};
+
+/// Parsed puzzle input of some day.
+pub type Puzzle = Box<dyn Any + Send>;
+
+type ParseFn = Box<dyn Fn(&Input) -> Result<Puzzle, AdventError> + Send + Sync>;
+type PartFn = Box<dyn Fn(&Puzzle) -> Result<Answer, AdventError> + Send + Sync>;
+type CommandFn = Box<dyn Fn(&mut Puzzle, &str) -> Result<String, AdventError> + Send + Sync>;
+type FigureFn = Box<dyn Fn(&Puzzle) -> Figure + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Result of a part, `None` meaning the puzzle has no answer.
+pub trait IntoAnswer {
+    fn into_answer(self) -> Option<Answer>;
+}
+
+macro_rules! number_answer {
+    ($($t:ty),*) => {
+        $(impl IntoAnswer for $t {
+            fn into_answer(self) -> Option<Answer> {
+                Some(Answer::Number(self as i64))
+            }
+        })*
+    };
+}
+
+number_answer!(i32, i64, u32, usize);
+
+impl IntoAnswer for String {
+    fn into_answer(self) -> Option<Answer> {
+        Some(Answer::Text(self))
+    }
+}
+
+impl<T: IntoAnswer> IntoAnswer for Option<T> {
+    fn into_answer(self) -> Option<Answer> {
+        self.and_then(T::into_answer)
+    }
+}
+
+/// Day specific operation on a parsed puzzle, `args` is the rest of the command line.
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub help: &'static str,
+    run: CommandFn,
+}
+
+impl Command {
+    pub fn run(&self, puzzle: &mut Puzzle, args: &str) -> Result<String, AdventError> {
+        (self.run)(puzzle, args)
+    }
+}
+
+/// Setting of the configuration a day reads, see [`config`](crate::config).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    /// Dotted key, as given to `--set`
+    pub name: String,
+    pub default: String,
+}
+
+pub struct Solver {
+    pub day: u8,
+    pub title: &'static str,
+    /// What the input looks like, in a sentence
+    pub input: &'static str,
+    /// Variant of the input in the data directory showing the format
+    pub example: &'static str,
+    pub params: Vec<Param>,
+    pub commands: Vec<Command>,
+    parse: ParseFn,
+    part1: PartFn,
+    part2: Option<PartFn>,
+    figure: Option<FigureFn>,
+}
+
+impl Solver {
+    pub fn parse(&self, input: &Input) -> Result<Puzzle, AdventError> {
+        (self.parse)(input)
+    }
+
+    pub fn part1(&self, puzzle: &Puzzle) -> Result<Answer, AdventError> {
+        (self.part1)(puzzle)
+    }
+
+    /// Fails with [`AdventError::NoAnswer`] for the days without a second part.
+    pub fn part2(&self, puzzle: &Puzzle) -> Result<Answer, AdventError> {
+        match &self.part2 {
+            Some(part2) => part2(puzzle),
+            None => Err(AdventError::NoAnswer { day: self.day, part: 2 }),
+        }
+    }
+
+    pub fn has_part2(&self) -> bool {
+        self.part2.is_some()
+    }
+
+    pub fn has_figure(&self) -> bool {
+        self.figure.is_some()
+    }
+
+    /// Picture of the puzzle, for the days which have one.
+    pub fn figure(&self, puzzle: &Puzzle) -> Option<Figure> {
+        self.figure.as_ref().map(|figure| figure(puzzle))
+    }
+
+    pub fn command(&self, name: &str) -> Option<&Command> {
+        self.commands.iter().find(|c| c.name == name)
+    }
+
+    fn with_command<P: Any>(mut self, name: &'static str, usage: &'static str, help: &'static str,
+                            run: fn(&mut P, &str) -> Result<String, AdventError>) -> Solver {
+        let run = Box::new(move |puzzle: &mut Puzzle, args: &str| run(downcast_mut(puzzle), args));
+        self.commands.push(Command { name, usage, help, run });
+        self
+    }
+
+    fn with_figure<P: Any>(mut self, figure: fn(&P) -> Figure) -> Solver {
+        self.figure = Some(Box::new(move |puzzle: &Puzzle| figure(downcast(puzzle))));
+        self
+    }
+
+    fn with_input(mut self, input: &'static str) -> Solver {
+        self.input = input;
+        self
+    }
+
+    fn with_example(mut self, variant: &'static str) -> Solver {
+        self.example = variant;
+        self
+    }
+
+    /// Lists the settings of `C` under the `dayN` table, with the values of `C::default()`.
+    fn with_params<C: Serialize + Default>(mut self) -> Solver {
+        let table = toml::Table::try_from(C::default()).expect("Day configurations are tables");
+        self.params = table.into_iter()
+            .map(|(key, value)| Param { name: format!("day{}.{}", self.day, key), default: value.to_string() })
+            .collect();
+        self
+    }
+}
+
+fn downcast<P: Any>(puzzle: &Puzzle) -> &P {
+    puzzle.downcast_ref().expect("Puzzle of another day")
+}
+
+fn downcast_mut<P: Any>(puzzle: &mut Puzzle) -> &mut P {
+    puzzle.downcast_mut().expect("Puzzle of another day")
+}
+
+fn solver<P, E, A, B>(day: u8, title: &'static str, parse: fn(&Input) -> Result<P, E>,
+                      part1: fn(&P) -> A, part2: fn(&P) -> B) -> Solver
+    where P: Any + Send, E: Into<AdventError> + 'static, A: IntoAnswer + 'static, B: IntoAnswer + 'static {
+    let answer = move |a: Option<Answer>, part: u8| a.ok_or(AdventError::NoAnswer { day, part });
+
+    Solver {
+        day,
+        title,
+        input: "",
+        example: "test",
+        params: vec![],
+        commands: vec![],
+        parse: Box::new(move |input| Ok(Box::new(parse(input).map_err(Into::into)?))),
+        part1: Box::new(move |p| answer(part1(downcast(p)).into_answer(), 1)),
+        part2: Some(Box::new(move |p| answer(part2(downcast(p)).into_answer(), 2))),
+        figure: None,
+    }
+}
+
+/// All the days, in order.
+pub fn solvers() -> &'static [Solver] {
+    static SOLVERS: OnceLock<Vec<Solver>> = OnceLock::new();
+
+    SOLVERS.get_or_init(|| vec![
+        solver(1, "Sonar Sweep", day_01::parse, |p| day_01::part1(p), |p| day_01::part2(p, &config::get().day1))
+            .with_input("Depth measurements, one number per line")
+            .with_params::<day_01::Config>(),
+        solver(2, "Dive!", day_02::parse, |p| day_02::part1(p), |p| day_02::part2(p))
+            .with_input("Submarine commands, one `forward|down|up <n>` per line"),
+        solver(3, "Binary Diagnostic", day_03::parse, |p| day_03::part1(p), |p| day_03::part2(p))
+            .with_input("Binary numbers of the same width, one per line"),
+        solver(4, "Giant Squid", day_04::parse, |(g, b)| day_04::part1(g, b), |(g, b)| day_04::part2(g, b))
+            .with_input("Comma-separated drawn numbers, then 5x5 boards separated by blank lines"),
+        solver(5, "Hydrothermal Venture", day_05::parse, |p| day_05::part1(p), |p| day_05::part2(p))
+            .with_input("Vent lines, one `x1,y1 -> x2,y2` per line"),
+        solver(6, "Lanternfish", day_06::parse, |p| day_06::part1(p, &config::get().day6), |p| day_06::part2(p, &config::get().day6))
+            .with_input("Comma-separated timers of the initial fish")
+            .with_params::<day_06::Config>(),
+        solver(7, "The Treachery of Whales", day_07::parse, |p| day_07::part1(p), |p| day_07::part2(p))
+            .with_input("Comma-separated horizontal positions of the crabs"),
+        solver(8, "Seven Segment Search", day_08::parse, |p| day_08::part1(p), |p| day_08::part2(p))
+            .with_input("Display notes, one `<ten signals> | <four outputs>` entry per line")
+            .with_command("decode", "decode <signals> | <outputs>", "Decodes a single display entry", decode_8),
+        solver(9, "Smoke Basin", parse_9, Grid9::part1, Grid9::part2)
+            .with_input("Heightmap of single digits, 5x10 or 100x100")
+            .with_figure(Grid9::figure),
+        solver(10, "Syntax Scoring", day_10::parse, |p| day_10::part1(p), |p| day_10::part2(p))
+            .with_input("Lines of brackets `()[]{}<>`"),
+        solver(11, "Dumbo Octopus", parse_11, |g| g.part1(&config::get().day11), |g| g.part2(&config::get().day11))
+            .with_input("Grid of single digit energy levels, 5x5 or 10x10")
+            .with_params::<day_11::Config>(),
+        solver(12, "Passage Pathing", day_12::parse, day_12::part1, day_12::part2)
+            .with_input("Cave connections, one `<cave>-<cave>` per line, big caves in upper case")
+            .with_example("small"),
+        solver(13, "Transparent Origami", day_13::parse, |(p, f)| day_13::part1(p, f), |(p, f)| day_13::part2(p, f))
+            .with_input("Dots, one `x,y` per line, then `fold along x=N` instructions after a blank line")
+            .with_command("fold", "fold <x|y>=<n>", "Folds the loaded paper once", fold_13)
+            .with_command("show", "show", "Draws the dots of the loaded paper", show_13)
+            .with_figure(figure_13),
+        solver(14, "Extended Polymerization", day_14::parse, |(t, i)| day_14::part1(t, i, &config::get().day14), |(t, i)| day_14::part2(t, i, &config::get().day14))
+            .with_input("Polymer template, then `AB -> C` insertion rules after a blank line")
+            .with_params::<day_14::Config>(),
+        solver(15, "Chiton", parse_15, Grid15::part1, Grid15::part2)
+            .with_input("Square risk level map of single digits, 10x10 or 100x100")
+            .with_command("path", "path <i>,<j> <i>,<j>", "Lowest total risk of a path between two positions", path_15)
+            .with_figure(Grid15::figure),
+    ])
+}
+
+pub fn find(day: u8) -> Option<&'static Solver> {
+    solvers().iter().find(|s| s.day == day)
+}
+
+/// Rows and columns of a grid input, along with an input which can still be read after that.
+fn grid_size(input: &Input) -> Result<(usize, usize, Input), AdventError> {
+    let text = input.read_to_string().map_err(InputError::from)?;
+    let rows = text.lines().count();
+    let cols = text.lines().next().map_or(0, |l| l.chars().count());
+
+    let input = match input {
+        Input::Path(_) => input.clone(),
+        _ => Input::Text(text),
+    };
+
+    Ok((rows, cols, input))
+}
+
+enum Grid9 {
+    S5x10(Matrix<u8, 5, 10>),
+    S100x100(Box<Matrix<u8, 100, 100>>),
+}
+
+fn parse_9(input: &Input) -> Result<Grid9, AdventError> {
+    match grid_size(input)? {
+        (5, 10, input) => Ok(Grid9::S5x10(day_09::parse(&input)?)),
+        (100, 100, input) => Ok(Grid9::S100x100(Box::new(day_09::parse(&input)?))),
+        (rows, cols, _) => Err(AdventError::UnsupportedSize { rows, cols }),
+    }
+}
+
+impl Grid9 {
+    fn part1(&self) -> u32 {
+        match self {
+            Grid9::S5x10(m) => day_09::part1(m),
+            Grid9::S100x100(m) => day_09::part1(m),
+        }
+    }
+
+    fn part2(&self) -> usize {
+        match self {
+            Grid9::S5x10(m) => day_09::part2(m),
+            Grid9::S100x100(m) => day_09::part2(m),
+        }
+    }
+
+    /// Basins in colours of their own, the heights of 9 between them left dark.
+    fn figure(&self) -> Figure {
+        fn basins<const N: usize, const M: usize>(m: &Matrix<u8, N, M>) -> Figure {
+            let mut figure = Figure::new("Smoke basins", N, M);
+            let basins = day_09::basins(m);
+
+            for pos in basins.index_iter() {
+                if let Some(basin) = basins.get(&pos) {
+                    // Golden angle steps keep neighbouring basins apart in hue
+                    figure.paint(pos.i(), pos.j(), &format!("hsl({},55%,{}%)", basin * 137 % 360, 35 + 3 * *m.get(&pos) as usize));
+                }
+            }
+
+            figure
+        }
+
+        match self {
+            Grid9::S5x10(m) => basins(m),
+            Grid9::S100x100(m) => basins(m),
+        }
+    }
+}
+
+enum Grid11 {
+    S5x5(Matrix<u8, 5, 5>),
+    S10x10(Matrix<u8, 10, 10>),
+}
+
+fn parse_11(input: &Input) -> Result<Grid11, AdventError> {
+    match grid_size(input)? {
+        (5, 5, input) => Ok(Grid11::S5x5(day_11::parse(&input)?)),
+        (10, 10, input) => Ok(Grid11::S10x10(day_11::parse(&input)?)),
+        (rows, cols, _) => Err(AdventError::UnsupportedSize { rows, cols }),
+    }
+}
+
+impl Grid11 {
+    fn part1(&self, config: &day_11::Config) -> usize {
+        match self {
+            Grid11::S5x5(m) => day_11::part1(m, config),
+            Grid11::S10x10(m) => day_11::part1(m, config),
+        }
+    }
+
+    fn part2(&self, config: &day_11::Config) -> Option<usize> {
+        match self {
+            Grid11::S5x5(m) => day_11::part2(m, config),
+            Grid11::S10x10(m) => day_11::part2(m, config),
+        }
+    }
+}
+
+enum Grid15 {
+    S10x10(Matrix<u8, 10, 10>),
+    S100x100(Box<Matrix<u8, 100, 100>>),
+}
+
+fn parse_15(input: &Input) -> Result<Grid15, AdventError> {
+    match grid_size(input)? {
+        (10, 10, input) => Ok(Grid15::S10x10(day_15::parse(&input)?)),
+        (100, 100, input) => Ok(Grid15::S100x100(Box::new(day_15::parse(&input)?))),
+        (rows, cols, _) => Err(AdventError::UnsupportedSize { rows, cols }),
+    }
+}
+
+impl Grid15 {
+    fn part1(&self) -> usize {
+        match self {
+            Grid15::S10x10(m) => day_15::part1(m),
+            Grid15::S100x100(m) => day_15::part1(m),
+        }
+    }
+
+    fn part2(&self) -> usize {
+        match self {
+            Grid15::S10x10(m) => day_15::part2::<10, 50>(m),
+            Grid15::S100x100(m) => day_15::part2::<100, 500>(m),
+        }
+    }
+
+    /// Risk levels shaded from dark to light with the lowest risk path on top.
+    fn figure(&self) -> Figure {
+        fn path<const N: usize>(m: &Matrix<u8, N, N>) -> Figure {
+            let mut figure = Figure::new("Lowest risk path", N, N);
+
+            for pos in m.index_iter() {
+                figure.paint(pos.i(), pos.j(), &format!("hsl(200,15%,{}%)", 8 + 4 * *m.get(&pos) as usize));
+            }
+            for pos in day_15::lowest_risk_path(m) {
+                figure.paint(pos.i(), pos.j(), "#ff5c33");
+            }
+
+            figure
+        }
+
+        match self {
+            Grid15::S10x10(m) => path(m),
+            Grid15::S100x100(m) => path(m),
+        }
+    }
+}
+
+fn decode_8(_: &mut Vec<day_08::Entry>, args: &str) -> Result<String, AdventError> {
+    let entry = match day_08::parse(&Input::text(args))?.pop() {
+        Some(entry) => entry,
+        None => return Err(AdventError::InvalidCommand("Expected an entry to decode".to_string())),
+    };
+
+    let cyphers = day_08::all_cyphers(day_08::permutations(&[0, 1, 2, 3, 4, 5, 6]));
+
+    match day_08::decode(&entry, &cyphers) {
+        Some(d) => Ok(format!("signals {}, outputs {}", d.signal_num(), d.output_num())),
+        None => Err(AdventError::InvalidCommand("No wiring matches the entry".to_string())),
+    }
+}
+
+fn fold_13((points, _): &mut (Vec<day_13::Point>, Vec<day_13::Fold>), args: &str) -> Result<String, AdventError> {
+    let fold = args.trim().split_once('=')
+        .and_then(|(axis, n)| day_13::Fold::try_from((axis.chars().next()?, n)).ok())
+        .ok_or_else(|| AdventError::InvalidCommand(format!("Expected a fold like x=5, got {:?}", args)))?;
+
+    let folded = day_13::fold(&points.iter().copied().collect(), &fold)
+        .ok_or_else(|| AdventError::InvalidCommand(format!("Folding along {:?} would put dots past the edge", fold)))?;
+    *points = folded.into_iter().collect();
+
+    Ok(format!("{} dots left", points.len()))
+}
+
+fn show_13((points, _): &mut (Vec<day_13::Point>, Vec<day_13::Fold>), _: &str) -> Result<String, AdventError> {
+    let points: HashSet<day_13::Point> = points.iter().copied().collect();
+
+    Ok(day_13::viz(&points).trim_end().to_string())
+}
+
+fn figure_13((points, folds): &(Vec<day_13::Point>, Vec<day_13::Fold>)) -> Figure {
+    let points = day_13::fold_all(points, folds, &mut NoObserver).unwrap_or_default();
+    let rows = points.iter().map(|p| p.y + 1).max().unwrap_or(0);
+    let cols = points.iter().map(|p| p.x + 1).max().unwrap_or(0);
+
+    let mut figure = Figure::new("Activation code", rows, cols);
+    for p in points {
+        figure.paint(p.y, p.x, "#ffff66");
+    }
+
+    figure
+}
+
+fn path_15(grid: &mut Grid15, args: &str) -> Result<String, AdventError> {
+    let positions: Vec<(usize, usize)> = args.split_whitespace()
+        .map(|p| p.split_once(',').and_then(|(i, j)| Some((i.parse().ok()?, j.parse().ok()?))))
+        .collect::<Option<_>>()
+        .filter(|p: &Vec<_>| p.len() == 2)
+        .ok_or_else(|| AdventError::InvalidCommand(format!("Expected two positions like 0,0 9,9, got {:?}", args)))?;
+
+    fn risk<const N: usize>(m: &Matrix<u8, N, N>, from: (usize, usize), to: (usize, usize)) -> Result<String, AdventError> {
+        if from.0.max(from.1).max(to.0).max(to.1) >= N {
+            return Err(AdventError::InvalidCommand(format!("Positions have to be within the {}x{} grid", N, N)));
+        }
+
+        let to = Pos::new(to.0, to.1);
+        Ok(day_15::distances_from(m, &Pos::new(from.0, from.1), &to).get(&to).to_string())
+    }
+
+    match grid {
+        Grid15::S10x10(m) => risk(m, positions[0], positions[1]),
+        Grid15::S100x100(m) => risk(m, positions[0], positions[1]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_solves_samples() {
+        let solver = find(13).unwrap();
+        let puzzle = solver.parse(&Input::path("data/13_folds_test.txt")).unwrap();
+
+        assert_eq!(solver.part1(&puzzle).unwrap(), Answer::Number(17));
+        assert_eq!(solvers().iter().map(|s| s.day).collect::<Vec<_>>(), (1..=15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_metadata() {
+        assert!(solvers().iter().all(|s| !s.input.is_empty() && s.has_part2()));
+        assert_eq!(find(6).unwrap().params.iter().map(|p| format!("{} = {}", p.name, p.default)).collect::<Vec<_>>(),
+                   vec!["day6.birth_days = 8", "day6.part1_days = 80", "day6.part2_days = 256", "day6.spawn_days = 6"]);
+
+        // Like the last day of the event, whose second star comes for free
+        let last = Solver { part2: None, ..solver(25, "Sea Cucumber", day_06::parse, |p| day_06::part1(p, &day_06::Config::default()), |_| 0) };
+        let puzzle = last.parse(&Input::text("3,4,3,1,2\n")).unwrap();
+        assert!(!last.has_part2());
+        assert!(matches!(last.part2(&puzzle), Err(AdventError::NoAnswer { day: 25, part: 2 })));
+    }
+
+    #[test]
+    fn test_grid_size_dispatch() {
+        let solver = find(11).unwrap();
+        let puzzle = solver.parse(&Input::text("11111\n19991\n19191\n19991\n11111\n")).unwrap();
+        assert_eq!(solver.part2(&puzzle).unwrap(), Answer::Number(6));
+
+        assert!(matches!(solver.parse(&Input::text("123\n456\n")), Err(AdventError::UnsupportedSize { rows: 2, cols: 3 })));
+    }
+
+    #[test]
+    fn test_figures() {
+        let solver = find(15).unwrap();
+        let text = std::fs::read_to_string("data/15_path_test.txt").unwrap();
+        let figure = solver.figure(&solver.parse(&Input::text(&text)).unwrap()).unwrap();
+
+        // The start doesn't count towards the risk
+        let risks: Vec<Vec<u32>> = text.lines().map(|l| l.chars().map(|c| c.to_digit(10).unwrap()).collect()).collect();
+        let path: Vec<(usize, usize)> = (0..10).flat_map(|i| (0..10).map(move |j| (i, j)))
+            .filter(|&(i, j)| figure.colour(i, j) == Some("#ff5c33"))
+            .collect();
+        assert_eq!(path.iter().map(|&(i, j)| risks[i][j]).sum::<u32>() - risks[0][0], 40);
+
+        assert!(find(1).unwrap().figure(&find(1).unwrap().parse(&Input::text("1\n")).unwrap()).is_none());
+    }
+}
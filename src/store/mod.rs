@@ -0,0 +1,261 @@
+//! Puzzle inputs by day and variant, downloading the missing ones.
+//!
+//! Inputs committed to the data directory are named `NN_name_variant.txt`, `12_caves_big.txt`
+//! is the `big` variant of day 12. Downloaded inputs go to the cache directory as
+//! `NN_variant.txt`. Only the `prod` variant, the personal puzzle input, can be downloaded:
+//! [`HttpFetcher`] gets it from the Advent of Code site with the session cookie found in
+//! `ADVENT_SESSION`, `ADVENT_URL` points it at another server.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use thiserror::Error;
+
+use crate::AdventError;
+
+pub const PROD: &str = "prod";
+const DEFAULT_URL: &str = "https://adventofcode.com/2021";
+
+#[derive(Parser)]
+pub struct SubCmd {
+    day: u8,
+
+    /// Which input of the day, `prod` is the one downloaded when missing
+    #[clap(default_value = PROD)]
+    variant: String,
+
+    #[clap(flatten)]
+    dirs: Dirs,
+}
+
+/// Where the inputs are, shared by the commands looking them up.
+#[derive(Parser)]
+pub struct Dirs {
+    /// Directory holding the NN_name_variant.txt inputs
+    #[clap(long, default_value = "data")]
+    pub data_dir: PathBuf,
+
+    /// Directory downloaded inputs are kept in
+    #[clap(long, default_value = "data/cache")]
+    pub cache_dir: PathBuf,
+}
+
+#[derive(Error, Debug)]
+pub enum FetchError {
+    #[error("The server answered {status} for the day {day} input")]
+    Status { day: u8, status: u16 },
+
+    #[error("Unable to download the day {day} input: {message}")]
+    Transport { day: u8, message: String },
+}
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("No {variant} input for day {day} in {}, set ADVENT_SESSION to download it", dir.display())]
+    Missing { day: u8, variant: String, dir: PathBuf },
+
+    #[error(transparent)]
+    FetchError(#[from] FetchError),
+
+    #[error("Unable to cache the input in {}: {source}", path.display())]
+    Cache { path: PathBuf, source: io::Error },
+}
+
+/// Source of the inputs missing from disk.
+pub trait Fetcher: Send + Sync {
+    fn fetch(&self, day: u8) -> Result<String, FetchError>;
+}
+
+pub struct HttpFetcher {
+    url: String,
+    session: String,
+}
+
+impl HttpFetcher {
+    /// Fetches from `url`, the address of the event, e.g. `https://adventofcode.com/2021`.
+    pub fn new(url: &str, session: &str) -> HttpFetcher {
+        HttpFetcher { url: url.trim_end_matches('/').to_string(), session: session.to_string() }
+    }
+
+    /// Fetcher configured by `ADVENT_SESSION` and `ADVENT_URL`, none without a session.
+    pub fn from_env() -> Option<HttpFetcher> {
+        let session = env::var("ADVENT_SESSION").ok().filter(|s| !s.trim().is_empty())?;
+        let url = env::var("ADVENT_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+
+        Some(HttpFetcher::new(&url, session.trim()))
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, day: u8) -> Result<String, FetchError> {
+        let res = ureq::get(&format!("{}/day/{}/input", self.url, day))
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", concat!("adventofcode/", env!("CARGO_PKG_VERSION")))
+            .call();
+
+        match res {
+            Ok(response) => response.into_string().map_err(|e| FetchError::Transport { day, message: e.to_string() }),
+            Err(ureq::Error::Status(status, _)) => Err(FetchError::Status { day, status }),
+            Err(e) => Err(FetchError::Transport { day, message: e.to_string() }),
+        }
+    }
+}
+
+pub struct Store {
+    data_dir: PathBuf,
+    cache_dir: PathBuf,
+    fetcher: Option<Box<dyn Fetcher>>,
+}
+
+impl Store {
+    /// Store of the inputs on disk, nothing gets downloaded until given a fetcher.
+    pub fn new<P: AsRef<Path>, C: AsRef<Path>>(data_dir: P, cache_dir: C) -> Store {
+        Store { data_dir: data_dir.as_ref().to_path_buf(), cache_dir: cache_dir.as_ref().to_path_buf(), fetcher: None }
+    }
+
+    /// Store of the directories, downloading with the [`HttpFetcher`] of the environment.
+    pub fn from_dirs(dirs: &Dirs) -> Store {
+        let store = Store::new(&dirs.data_dir, &dirs.cache_dir);
+
+        match HttpFetcher::from_env() {
+            Some(fetcher) => store.with_fetcher(fetcher),
+            None => store,
+        }
+    }
+
+    pub fn with_fetcher<F: Fetcher + 'static>(mut self, fetcher: F) -> Store {
+        self.fetcher = Some(Box::new(fetcher));
+        self
+    }
+
+    /// Input already on disk, the data directory wins over the cache.
+    pub fn find(&self, day: u8, variant: &str) -> Option<PathBuf> {
+        let (prefix, suffix) = (format!("{:02}_", day), format!("_{}.txt", variant));
+        let mut inputs: Vec<PathBuf> = fs::read_dir(&self.data_dir).into_iter().flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(&suffix)))
+            .collect();
+        inputs.sort();
+
+        inputs.into_iter().next().or_else(|| Some(self.cached(day, variant)).filter(|p| p.is_file()))
+    }
+
+    /// Input on disk, downloading the `prod` one into the cache when missing.
+    pub fn resolve(&self, day: u8, variant: &str) -> Result<PathBuf, StoreError> {
+        if let Some(path) = self.find(day, variant) {
+            return Ok(path);
+        }
+
+        let fetcher = match &self.fetcher {
+            Some(fetcher) if variant == PROD => fetcher,
+            _ => return Err(StoreError::Missing { day, variant: variant.to_string(), dir: self.data_dir.clone() }),
+        };
+
+        let input = fetcher.fetch(day)?;
+        let path = self.cached(day, variant);
+        // Written aside and renamed, an interrupted download never leaves half an input behind
+        let partial = path.with_extension("part");
+        fs::create_dir_all(&self.cache_dir)
+            .and_then(|_| fs::write(&partial, input))
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|source| StoreError::Cache { path: path.clone(), source })?;
+
+        Ok(path)
+    }
+
+    fn cached(&self, day: u8, variant: &str) -> PathBuf {
+        self.cache_dir.join(format!("{:02}_{}.txt", day, variant))
+    }
+}
+
+pub fn main(args: SubCmd) -> Result<(), AdventError> {
+    let path = Store::from_dirs(&args.dirs).resolve(args.day, &args.variant)?;
+    println!("{}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use super::*;
+
+    /// Serves `input` as the input of every day to the `secret` session, counting the requests.
+    fn stub_server(input: &'static str, secret: &'static str) -> (SocketAddr, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let lines: Vec<String> = BufReader::new(stream.try_clone().unwrap()).lines()
+                    .map(|l| l.unwrap())
+                    .take_while(|l| !l.is_empty())
+                    .collect();
+                let authorized = lines.iter().any(|l| l.eq_ignore_ascii_case(&format!("cookie: session={}", secret)));
+
+                let (status, body) = match (lines[0].split(' ').nth(1), authorized) {
+                    (Some(path), true) if path.ends_with("/input") => ("200 OK", input),
+                    (_, true) => ("404 Not Found", "404 Not Found"),
+                    (_, false) => ("400 Bad Request", "Puzzle inputs differ by user.  Please log in to get your puzzle input."),
+                };
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+            }
+        });
+
+        (addr, requests)
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("advent-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_finds_variants() {
+        let store = Store::new("data", cache_dir("variants"));
+
+        assert_eq!(store.find(12, PROD), Some(PathBuf::from("data/12_caves_prod.txt")));
+        assert_eq!(store.find(12, "big"), Some(PathBuf::from("data/12_caves_big.txt")));
+        assert_eq!(store.find(11, "test2"), Some(PathBuf::from("data/11_octopusses_test2.txt")));
+        assert_eq!(store.find(26, PROD), None);
+        assert!(matches!(store.resolve(26, PROD), Err(StoreError::Missing { day: 26, .. })));
+    }
+
+    #[test]
+    fn test_downloads_once() {
+        let (addr, requests) = stub_server("3,4,3,1,2\n", "cookie");
+        let cache = cache_dir("download");
+        let store = Store::new("data", &cache).with_fetcher(HttpFetcher::new(&format!("http://{}/2021/", addr), "cookie"));
+
+        let path = store.resolve(26, PROD).unwrap();
+        assert_eq!(path, cache.join("26_prod.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3,4,3,1,2\n");
+
+        assert_eq!(store.resolve(26, PROD).unwrap(), path);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Examples are not downloadable, nor are the inputs of somebody else
+        assert!(matches!(store.resolve(26, "test"), Err(StoreError::Missing { .. })));
+        let stranger = Store::new("data", cache_dir("stranger")).with_fetcher(HttpFetcher::new(&format!("http://{}/2021", addr), "other"));
+        assert!(matches!(stranger.resolve(26, PROD), Err(StoreError::FetchError(FetchError::Status { day: 26, status: 400 }))));
+
+        fs::remove_dir_all(cache).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+//! Re-runs a command whenever one of its files changes.
+//!
+//! Changes are found by polling modification times with [`std::fs::metadata`], which works the
+//! same everywhere and is plenty fast for the handful of input files a run reads.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::AdventError;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Modification times of a set of files, a missing file counts as a state of its own.
+pub struct Watcher {
+    files: Vec<PathBuf>,
+    stamps: Vec<Option<SystemTime>>,
+}
+
+impl Watcher {
+    pub fn new(files: Vec<PathBuf>) -> Watcher {
+        let stamps = files.iter().map(|f| modified(f)).collect();
+
+        Watcher { files, stamps }
+    }
+
+    /// Files which changed since the previous call, or since the watcher was created.
+    pub fn changed(&mut self) -> Vec<&PathBuf> {
+        let mut changed = vec![];
+
+        for (file, stamp) in self.files.iter().zip(self.stamps.iter_mut()) {
+            let current = modified(file);
+            if current != *stamp {
+                *stamp = current;
+                changed.push(file);
+            }
+        }
+
+        changed
+    }
+
+    /// Blocks until at least one of the files changes and returns the changed ones.
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let mut changed: Vec<PathBuf> = self.changed().into_iter().cloned().collect();
+            if changed.is_empty() {
+                continue;
+            }
+
+            // Editors often save in several writes, let them finish before reading
+            thread::sleep(POLL_INTERVAL);
+            for file in self.changed() {
+                if !changed.contains(file) {
+                    changed.push(file.clone());
+                }
+            }
+
+            return changed;
+        }
+    }
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+fn names(files: &[PathBuf]) -> String {
+    files.iter().map(|f| f.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Lines which differ between two outputs, the old ones prefixed with `-` and the new with `+`.
+pub fn diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, answers are a few lines long
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut res = vec![];
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            res.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            res.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+
+    res
+}
+
+/**
+Runs `f`, then runs it again every time one of the files changes, forever. `f` prints whatever
+it wants and returns the part of its output to compare between runs, the differences are printed
+after each re-run. Errors are printed and don't stop watching, the next edit may fix them.
+ **/
+pub fn watch<F>(files: Vec<PathBuf>, mut f: F) -> !
+    where F: FnMut() -> Result<String, AdventError> {
+    let mut watcher = Watcher::new(files);
+    let mut previous: Option<String> = None;
+
+    loop {
+        match f() {
+            Ok(output) => {
+                if let Some(previous) = &previous {
+                    let changes = diff(previous, &output);
+                    if changes.is_empty() {
+                        println!("(answers unchanged)");
+                    } else {
+                        println!("Changes since the previous run:\n{}", changes.join("\n"));
+                    }
+                }
+                previous = Some(output);
+            }
+            Err(e) => eprintln!("error: {}", e),
+        }
+
+        eprintln!("Watching {} for changes, Ctrl-C to stop", names(&watcher.files));
+        let changed = watcher.wait();
+        eprintln!("\n{} changed", names(&changed));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        assert!(diff("a\nb\n", "a\nb\n").is_empty());
+        assert_eq!(diff("a: 1\nb: 2\nc: 3\n", "a: 1\nb: 5\nc: 3\nd: 4\n"), vec!["- b: 2", "+ b: 5", "+ d: 4"]);
+    }
+
+    #[test]
+    fn test_watcher_sees_changes() {
+        let path = std::env::temp_dir().join(format!("advent-watch-{}.txt", std::process::id()));
+        fs::write(&path, "1").unwrap();
+
+        let mut watcher = Watcher::new(vec![path.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.changed(), vec![&path]);
+        assert!(watcher.changed().is_empty());
+    }
+}